@@ -0,0 +1,60 @@
+use display_interface::DisplayError;
+
+use crate::{FsmcLcd, Rect};
+
+impl<'d> FsmcLcd<'d> {
+    /// Copies rectangular region of an image to the display
+    ///
+    /// `src` holds image rows of `stride` pixels each, the region occupies
+    /// first `rect.width` pixels of first `rect.height` rows. Rows are
+    /// streamed directly from `src`, no intermediate buffer is used.
+    ///
+    /// Region is clipped against panel size (see [`FsmcLcd::set_panel_size`]),
+    /// so it may be partially or completely off-screen.
+    ///
+    /// Returns [`DisplayError::OutOfBoundsError`] if `src` is too short to
+    /// hold the region or `stride` is less than region width.
+    pub fn blit(&self, rect: Rect, src: &[u16], stride: usize) -> Result<(), DisplayError> {
+        if rect.is_empty() {
+            return Ok(());
+        }
+
+        check_source(&rect, src.len(), stride)?;
+
+        let Some(clipped) = rect.intersection(&self.bounds()) else {
+            return Ok(());
+        };
+
+        // Offset of clipped region inside of source region
+        let dx = (clipped.x - rect.x) as usize;
+        let dy = (clipped.y - rect.y) as usize;
+        let clipped_width = clipped.width as usize;
+
+        self.write_window(
+            clipped.x as u16,
+            clipped.y as u16,
+            (clipped.x + clipped.width as i32 - 1) as u16,
+            (clipped.y + clipped.height as i32 - 1) as u16,
+        );
+        for row in src[dy * stride..].chunks(stride).take(clipped.height as usize) {
+            for pixel in &row[dx..dx + clipped_width] {
+                self.write_data(*pixel);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that image of `len` pixels with given `stride` holds region
+///
+/// Region must not be empty.
+pub(crate) fn check_source(rect: &Rect, len: usize, stride: usize) -> Result<(), DisplayError> {
+    let width = rect.width as usize;
+    let needed = (rect.height as usize - 1)
+        .checked_mul(stride)
+        .and_then(|offset| offset.checked_add(width));
+    match needed {
+        Some(needed) if stride >= width && len >= needed => Ok(()),
+        _ => Err(DisplayError::OutOfBoundsError),
+    }
+}
@@ -0,0 +1,158 @@
+//! Automatic timing calibration
+//!
+//! [`FsmcLcd::calibrate_timing`] steps FSMC timings from slow to fast,
+//! writing test patterns to display memory and reading them back at each
+//! step, and finds the fastest timings at which transfers are still stable.
+//!
+//! Write timing is calibrated first, using slowest read timing, then read
+//! timing is calibrated using found write timing. If separate write timing
+//! is not configured, single timing used for both reads and writes is
+//! calibrated and returned as both read and write timing. Controller must be
+//! initialized with 16-bit pixel format, first 32 pixels of the top row are
+//! overwritten.
+//!
+//! Calibration depends on AHB clock frequency, so it must be repeated when
+//! clock configuration changes. Results can be stored using
+//! [`Timing::to_bits`] and restored with [`Timing::from_bits`].
+
+use crate::{FsmcLcd, Rect, Timing};
+
+/// Test patterns, chosen to switch as many lines as possible between pixels
+const PATTERNS: [u16; 32] = [
+    0x0000, 0xFFFF, 0x0000, 0xAAAA, 0x5555, 0xAAAA, 0x5555, 0xFFFF,
+    0x00FF, 0xFF00, 0x0F0F, 0xF0F0, 0x3333, 0xCCCC, 0x6666, 0x9999,
+    0x0001, 0x0002, 0x0004, 0x0008, 0x0010, 0x0020, 0x0040, 0x0080,
+    0x0100, 0x0200, 0x0400, 0x0800, 0x1000, 0x2000, 0x4000, 0x8000,
+];
+
+/// Calibration steps from slow to fast, as (address setup, data) phase
+/// durations in HCLK cycles
+const STEPS: [(u8, u8); 14] = [
+    (15, 255), (15, 128), (15, 64), (8, 32), (4, 16), (2, 12), (2, 8),
+    (1, 6), (1, 5), (1, 4), (0, 4), (0, 3), (0, 2), (0, 1),
+];
+
+/// Calibration parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CalibrationConfig {
+    /// Number of HCLK cycles added to address setup and data phases of the
+    /// fastest stable timing
+    pub margin: u8,
+    /// Number of times test patterns are written and read back at each step
+    pub iterations: u8,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> Self {
+        Self {
+            margin: 1,
+            iterations: 4,
+        }
+    }
+}
+
+/// Calibrated timings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Calibration {
+    /// Timing for read operations
+    pub read: Timing,
+    /// Timing for write operations
+    pub write: Timing,
+}
+
+/// Makes timing for calibration step, keeping access mode and bus
+/// turnaround of `base`
+fn step_timing(base: &Timing, (address_setup, data): (u8, u8)) -> Timing {
+    Timing {
+        data,
+        address_hold: Timing::ADDRESS_HOLD_MIN,
+        address_setup,
+        ..*base
+    }
+}
+
+/// Adds safety margin to timing
+fn with_margin(timing: Timing, margin: u8) -> Timing {
+    Timing {
+        data: timing.data.saturating_add(margin),
+        address_setup: timing
+            .address_setup
+            .saturating_add(margin)
+            .min(Timing::ADDRESS_SETUP_MAX),
+        ..timing
+    }
+}
+
+impl<'d> FsmcLcd<'d> {
+    /// Writes test patterns and checks that they are read back intact
+    fn verify_patterns(&self, iterations: u8) -> bool {
+        let rect = Rect::new(0, 0, PATTERNS.len() as u32, 1);
+        let mut readings = [0u16; PATTERNS.len()];
+        (0..iterations).all(|_| {
+            self.write_window(0, 0, PATTERNS.len() as u16 - 1, 0);
+            for pattern in PATTERNS {
+                self.write_data(pattern);
+            }
+            self.read_pixels(rect, &mut readings).is_ok() && readings == PATTERNS
+        })
+    }
+
+    /// Finds the fastest stable step, applying each step with `apply`
+    fn fastest_stable(
+        &mut self,
+        base: &Timing,
+        iterations: u8,
+        apply: fn(&mut Self, &Timing),
+    ) -> Option<Timing> {
+        let mut fastest = None;
+        for step in STEPS {
+            let timing = step_timing(base, step);
+            apply(self, &timing);
+            if !self.verify_patterns(iterations) {
+                break;
+            }
+            fastest = Some(timing);
+        }
+        fastest
+    }
+
+    /// Calibrates write timing first, then read timing
+    fn find_timings(
+        &mut self,
+        read: &Timing,
+        write: &Timing,
+        config: &CalibrationConfig,
+    ) -> Option<Calibration> {
+        if !self.has_write_timing() {
+            let timing = self.fastest_stable(read, config.iterations, Self::set_read_timing)?;
+            let timing = with_margin(timing, config.margin);
+            return Some(Calibration { read: timing, write: timing });
+        }
+
+        self.set_read_timing(&step_timing(read, STEPS[0]));
+        let write = self.fastest_stable(write, config.iterations, Self::set_write_timing)?;
+        let write = with_margin(write, config.margin);
+
+        self.set_write_timing(&write);
+        let read = self.fastest_stable(read, config.iterations, Self::set_read_timing)?;
+        let read = with_margin(read, config.margin);
+
+        Some(Calibration { read, write })
+    }
+
+    /// Finds the fastest stable read and write timings
+    ///
+    /// See [module documentation](crate::calibrate) for details. Timings
+    /// which were in effect before calibration are restored afterwards.
+    /// Returns `None` if transfers fail even at the slowest timing.
+    pub fn calibrate_timing(&mut self, config: &CalibrationConfig) -> Option<Calibration> {
+        let original_read = self.read_timing();
+        let original_write = self.write_timing();
+
+        let result = self.find_timings(&original_read, &original_write, config);
+
+        self.set_read_timing(&original_read);
+        self.set_write_timing(&original_write);
+        result
+    }
+}
@@ -0,0 +1,155 @@
+use embassy_stm32::pac::fsmc::vals::Accmod;
+
+use crate::Timing;
+
+/// Type of memory the bank is configured for
+///
+/// Both types work with usual LCD controllers, some controllers are
+/// documented to be used with one of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryType {
+    /// SRAM/PSRAM
+    Sram,
+    /// NOR flash, with flash access enabled
+    Nor,
+}
+
+/// Active level of the NWAIT signal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitPolarity {
+    /// Bus is stalled while NWAIT is low
+    ActiveLow,
+    /// Bus is stalled while NWAIT is high
+    ActiveHigh,
+}
+
+/// Error in FSMC configuration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// Timing value is out of allowed range
+    InvalidTiming,
+    /// Separate write timing is given, but writes are disabled
+    WriteTimingWithoutWrites,
+    /// Data phase is too short to sample NWAIT signal
+    DataPhaseTooShortForWait,
+    /// NWAIT signal is enabled, but NWAIT pin is not given
+    MissingNwaitPin,
+    /// NWAIT pin is given, but NWAIT signal is not enabled
+    NwaitNotEnabled,
+    /// Address line doesn't exist or is used twice
+    InvalidAddressLine,
+}
+
+/// FSMC bank configuration
+///
+/// Default configuration matches the one used by [`FsmcLcd::new`]: NOR
+/// memory type, separate read and write timings, NWAIT signal ignored,
+/// writes enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use embassy_stm32_fsmc_display_interface::{FsmcLcdConfig, MemoryType, Timing};
+///
+/// // Single timing for both reads and writes
+/// let config = FsmcLcdConfig::new()
+///     .memory_type(MemoryType::Sram)
+///     .timing(Timing::default());
+/// ```
+///
+/// [`FsmcLcd::new`]: crate::FsmcLcd::new
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FsmcLcdConfig {
+    pub(crate) read_timing: Timing,
+    pub(crate) write_timing: Option<Timing>,
+    pub(crate) memory_type: MemoryType,
+    pub(crate) wait: Option<WaitPolarity>,
+    pub(crate) write_enable: bool,
+}
+
+impl FsmcLcdConfig {
+    /// Minimum data phase duration when NWAIT is used, in HCLK cycles
+    const WAIT_DATA_MIN: u8 = 4;
+
+    /// Creates default configuration
+    pub const fn new() -> Self {
+        const DEFAULT_TIMING: Timing = Timing::new(
+            Accmod::C,
+            Timing::BUS_TURNAROUND_MAX,
+            255,
+            Timing::ADDRESS_HOLD_MAX,
+            Timing::ADDRESS_SETUP_MAX,
+        );
+        Self {
+            read_timing: DEFAULT_TIMING,
+            write_timing: Some(DEFAULT_TIMING),
+            memory_type: MemoryType::Nor,
+            wait: None,
+            write_enable: true,
+        }
+    }
+
+    /// Uses single timing for both reads and writes
+    ///
+    /// Extended mode is disabled, access mode of the timing is ignored.
+    pub const fn timing(mut self, timing: Timing) -> Self {
+        self.read_timing = timing;
+        self.write_timing = None;
+        self
+    }
+
+    /// Uses separate timings for reads and writes (extended mode)
+    pub const fn read_write_timing(mut self, read: Timing, write: Timing) -> Self {
+        self.read_timing = read;
+        self.write_timing = Some(write);
+        self
+    }
+
+    /// Sets memory type
+    pub const fn memory_type(mut self, memory_type: MemoryType) -> Self {
+        self.memory_type = memory_type;
+        self
+    }
+
+    /// Enables NWAIT signal with given polarity, or disables it
+    ///
+    /// When enabled, FSMC stalls asynchronous transfers while NWAIT is
+    /// active. Data phase must be at least 4 HCLK cycles plus maximum time
+    /// controller takes to assert NWAIT, see reference manual.
+    pub const fn wait(mut self, wait: Option<WaitPolarity>) -> Self {
+        self.wait = wait;
+        self
+    }
+
+    /// Enables or disables write operations
+    pub const fn write_enable(mut self, write_enable: bool) -> Self {
+        self.write_enable = write_enable;
+        self
+    }
+
+    /// Returns all configured timings
+    fn timings(&self) -> impl Iterator<Item = &Timing> {
+        core::iter::once(&self.read_timing).chain(self.write_timing.as_ref())
+    }
+
+    /// Checks that configuration is consistent
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !self.timings().all(Timing::is_valid) {
+            return Err(ConfigError::InvalidTiming);
+        }
+        if self.write_timing.is_some() && !self.write_enable {
+            return Err(ConfigError::WriteTimingWithoutWrites);
+        }
+        let wait_sampled = self.timings().all(|timing| timing.data >= Self::WAIT_DATA_MIN);
+        if self.wait.is_some() && !wait_sampled {
+            return Err(ConfigError::DataPhaseTooShortForWait);
+        }
+        Ok(())
+    }
+}
+
+impl Default for FsmcLcdConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,471 @@
+//! MIPI Display Command Set (DCS) layer
+//!
+//! Most controllers used with FSMC (ILI9341, ST7789, ILI9486, HX8357, ...)
+//! implement the standard MIPI DCS commands. This module provides typed
+//! commands and parameters for them, so drivers don't have to assemble
+//! raw command and parameter bytes by hand.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use embassy_stm32_fsmc_display_interface::FsmcLcd;
+//! use embassy_stm32_fsmc_display_interface::dcs::{AddressMode, Command, PixelFormat};
+//! # let lcd: FsmcLcd<'static> = todo!();
+//!
+//! lcd.write_dcs(Command::ExitSleepMode);
+//! lcd.write_dcs(Command::SetPixelFormat(PixelFormat::Rgb565));
+//! lcd.write_dcs(Command::SetAddressMode(AddressMode::new().swap_xy(true).bgr(true)));
+//! lcd.write_dcs(Command::SetDisplayOn);
+//!
+//! // Fill 10x10 square in the top left corner with white
+//! lcd.write_window(0, 0, 9, 9);
+//! for _ in 0..100 {
+//!     lcd.write_data(0xffff);
+//! }
+//! ```
+
+use crate::FsmcLcd;
+
+/// DCS instruction codes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Instruction {
+    /// No operation
+    Nop = 0x00,
+    /// Resets controller registers to defaults
+    SoftReset = 0x01,
+    /// Reads manufacturer, version and driver IDs
+    ReadDisplayId = 0x04,
+    /// Reads power mode
+    ReadPowerMode = 0x0A,
+    /// Reads memory access mode (MADCTL)
+    ReadAddressMode = 0x0B,
+    /// Reads pixel format (COLMOD)
+    ReadPixelFormat = 0x0C,
+    /// Enters sleep mode
+    EnterSleepMode = 0x10,
+    /// Exits sleep mode
+    ExitSleepMode = 0x11,
+    /// Enters partial display mode
+    EnterPartialMode = 0x12,
+    /// Enters normal display mode
+    EnterNormalMode = 0x13,
+    /// Turns color inversion off
+    ExitInvertMode = 0x20,
+    /// Turns color inversion on
+    EnterInvertMode = 0x21,
+    /// Turns display output off
+    SetDisplayOff = 0x28,
+    /// Turns display output on
+    SetDisplayOn = 0x29,
+    /// Sets column address window
+    SetColumnAddress = 0x2A,
+    /// Sets page (row) address window
+    SetPageAddress = 0x2B,
+    /// Starts memory write
+    WriteMemoryStart = 0x2C,
+    /// Starts memory read
+    ReadMemoryStart = 0x2E,
+    /// Turns tearing effect output off
+    SetTearOff = 0x34,
+    /// Turns tearing effect output on
+    SetTearOn = 0x35,
+    /// Sets memory access mode (MADCTL)
+    SetAddressMode = 0x36,
+    /// Sets vertical scroll start line
+    SetScrollStart = 0x37,
+    /// Exits idle (reduced color) mode
+    ExitIdleMode = 0x38,
+    /// Enters idle (reduced color) mode
+    EnterIdleMode = 0x39,
+    /// Sets pixel format (COLMOD)
+    SetPixelFormat = 0x3A,
+    /// Continues memory write
+    WriteMemoryContinue = 0x3C,
+    /// Continues memory read
+    ReadMemoryContinue = 0x3E,
+    /// Sets scanline on which TE signal is activated
+    SetTearScanline = 0x44,
+    /// Reads current scanline
+    GetScanline = 0x45,
+}
+
+/// Memory access mode (MADCTL register)
+///
+/// Controls memory scan direction and RGB/BGR order, which is how display
+/// orientation is usually set up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AddressMode(u8);
+
+impl AddressMode {
+    const MY: u8 = 1 << 7;
+    const MX: u8 = 1 << 6;
+    const MV: u8 = 1 << 5;
+    const ML: u8 = 1 << 4;
+    const BGR: u8 = 1 << 3;
+    const MH: u8 = 1 << 2;
+
+    /// Creates address mode with all flags cleared
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Creates address mode from raw MADCTL value
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    /// Returns raw MADCTL value
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    const fn with(self, flag: u8, value: bool) -> Self {
+        if value { Self(self.0 | flag) } else { Self(self.0 & !flag) }
+    }
+
+    const fn has(self, flag: u8) -> bool {
+        self.0 & flag != 0
+    }
+
+    /// Sets row address order to bottom-to-top (MY)
+    pub const fn mirror_y(self, value: bool) -> Self {
+        self.with(Self::MY, value)
+    }
+
+    /// Sets column address order to right-to-left (MX)
+    pub const fn mirror_x(self, value: bool) -> Self {
+        self.with(Self::MX, value)
+    }
+
+    /// Exchanges rows and columns (MV)
+    pub const fn swap_xy(self, value: bool) -> Self {
+        self.with(Self::MV, value)
+    }
+
+    /// Refreshes panel from bottom to top (ML)
+    pub const fn vertical_refresh_bottom_to_top(self, value: bool) -> Self {
+        self.with(Self::ML, value)
+    }
+
+    /// Uses BGR instead of RGB subpixel order
+    pub const fn bgr(self, value: bool) -> Self {
+        self.with(Self::BGR, value)
+    }
+
+    /// Refreshes panel from right to left (MH)
+    pub const fn horizontal_refresh_right_to_left(self, value: bool) -> Self {
+        self.with(Self::MH, value)
+    }
+
+    /// Whether row address order is bottom-to-top
+    pub const fn is_mirror_y(self) -> bool {
+        self.has(Self::MY)
+    }
+
+    /// Whether column address order is right-to-left
+    pub const fn is_mirror_x(self) -> bool {
+        self.has(Self::MX)
+    }
+
+    /// Whether rows and columns are exchanged
+    pub const fn is_swap_xy(self) -> bool {
+        self.has(Self::MV)
+    }
+
+    /// Whether BGR subpixel order is used
+    pub const fn is_bgr(self) -> bool {
+        self.has(Self::BGR)
+    }
+}
+
+/// Pixel format of the MCU interface (COLMOD register)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 16 bits per pixel, one bus word per pixel
+    Rgb565,
+    /// 18 bits per pixel
+    Rgb666,
+    /// 24 bits per pixel
+    Rgb888,
+}
+
+impl PixelFormat {
+    /// Returns COLMOD parameter value, same format for both DPI and DBI
+    pub const fn bits(self) -> u8 {
+        match self {
+            Self::Rgb565 => 0x55,
+            Self::Rgb666 => 0x66,
+            Self::Rgb888 => 0x77,
+        }
+    }
+
+    /// Decodes COLMOD value, only DBI (MCU interface) part is used
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        match bits & 0b111 {
+            0b101 => Some(Self::Rgb565),
+            0b110 => Some(Self::Rgb666),
+            0b111 => Some(Self::Rgb888),
+            _ => None,
+        }
+    }
+}
+
+/// Tearing effect output mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TearingMode {
+    /// TE signals vertical blanking only
+    VBlank,
+    /// TE signals both vertical and horizontal blanking
+    VAndHBlank,
+}
+
+/// DCS command with its parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// No operation
+    Nop,
+    /// Resets controller registers to defaults
+    SoftReset,
+    /// Enters sleep mode
+    EnterSleepMode,
+    /// Exits sleep mode
+    ExitSleepMode,
+    /// Enters partial display mode
+    EnterPartialMode,
+    /// Enters normal display mode
+    EnterNormalMode,
+    /// Turns color inversion off
+    ExitInvertMode,
+    /// Turns color inversion on
+    EnterInvertMode,
+    /// Turns display output off
+    SetDisplayOff,
+    /// Turns display output on
+    SetDisplayOn,
+    /// Sets column address window, both bounds are inclusive
+    SetColumnAddress {
+        /// First column
+        start: u16,
+        /// Last column
+        end: u16,
+    },
+    /// Sets page (row) address window, both bounds are inclusive
+    SetPageAddress {
+        /// First row
+        start: u16,
+        /// Last row
+        end: u16,
+    },
+    /// Starts memory write, pixel data follows
+    WriteMemoryStart,
+    /// Continues memory write from the last written location
+    WriteMemoryContinue,
+    /// Turns tearing effect output off
+    SetTearOff,
+    /// Turns tearing effect output on in given mode
+    SetTearOn(TearingMode),
+    /// Sets memory access mode (MADCTL)
+    SetAddressMode(AddressMode),
+    /// Sets vertical scroll start line
+    SetScrollStart(u16),
+    /// Exits idle (reduced color) mode
+    ExitIdleMode,
+    /// Enters idle (reduced color) mode
+    EnterIdleMode,
+    /// Sets pixel format (COLMOD)
+    SetPixelFormat(PixelFormat),
+    /// Sets scanline on which TE signal is activated
+    SetTearScanline(u16),
+}
+
+impl Command {
+    /// Returns instruction code of the command
+    pub const fn instruction(&self) -> Instruction {
+        match self {
+            Self::Nop => Instruction::Nop,
+            Self::SoftReset => Instruction::SoftReset,
+            Self::EnterSleepMode => Instruction::EnterSleepMode,
+            Self::ExitSleepMode => Instruction::ExitSleepMode,
+            Self::EnterPartialMode => Instruction::EnterPartialMode,
+            Self::EnterNormalMode => Instruction::EnterNormalMode,
+            Self::ExitInvertMode => Instruction::ExitInvertMode,
+            Self::EnterInvertMode => Instruction::EnterInvertMode,
+            Self::SetDisplayOff => Instruction::SetDisplayOff,
+            Self::SetDisplayOn => Instruction::SetDisplayOn,
+            Self::SetColumnAddress { .. } => Instruction::SetColumnAddress,
+            Self::SetPageAddress { .. } => Instruction::SetPageAddress,
+            Self::WriteMemoryStart => Instruction::WriteMemoryStart,
+            Self::WriteMemoryContinue => Instruction::WriteMemoryContinue,
+            Self::SetTearOff => Instruction::SetTearOff,
+            Self::SetTearOn(_) => Instruction::SetTearOn,
+            Self::SetAddressMode(_) => Instruction::SetAddressMode,
+            Self::SetScrollStart(_) => Instruction::SetScrollStart,
+            Self::ExitIdleMode => Instruction::ExitIdleMode,
+            Self::EnterIdleMode => Instruction::EnterIdleMode,
+            Self::SetPixelFormat(_) => Instruction::SetPixelFormat,
+            Self::SetTearScanline(_) => Instruction::SetTearScanline,
+        }
+    }
+
+    /// Encodes command parameters into `buf`, returns number of parameters
+    pub fn encode_params(&self, buf: &mut [u8; 4]) -> usize {
+        match *self {
+            Self::SetColumnAddress { start, end } | Self::SetPageAddress { start, end } => {
+                buf[..2].copy_from_slice(&start.to_be_bytes());
+                buf[2..].copy_from_slice(&end.to_be_bytes());
+                4
+            }
+            Self::SetTearOn(mode) => {
+                buf[0] = match mode {
+                    TearingMode::VBlank => 0,
+                    TearingMode::VAndHBlank => 1,
+                };
+                1
+            }
+            Self::SetAddressMode(mode) => {
+                buf[0] = mode.bits();
+                1
+            }
+            Self::SetScrollStart(line) | Self::SetTearScanline(line) => {
+                buf[..2].copy_from_slice(&line.to_be_bytes());
+                2
+            }
+            Self::SetPixelFormat(format) => {
+                buf[0] = format.bits();
+                1
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// Display power mode, result of "read display power mode" command
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PowerMode(u8);
+
+impl PowerMode {
+    /// Creates power mode from raw value
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    /// Returns raw value
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Whether booster voltage is on
+    pub const fn booster_on(self) -> bool {
+        self.0 & (1 << 7) != 0
+    }
+
+    /// Whether idle mode is on
+    pub const fn idle_mode(self) -> bool {
+        self.0 & (1 << 6) != 0
+    }
+
+    /// Whether partial mode is on
+    pub const fn partial_mode(self) -> bool {
+        self.0 & (1 << 5) != 0
+    }
+
+    /// Whether controller is out of sleep mode
+    pub const fn sleep_out(self) -> bool {
+        self.0 & (1 << 4) != 0
+    }
+
+    /// Whether normal mode is on
+    pub const fn normal_mode(self) -> bool {
+        self.0 & (1 << 3) != 0
+    }
+
+    /// Whether display is on
+    pub const fn display_on(self) -> bool {
+        self.0 & (1 << 2) != 0
+    }
+}
+
+/// Display identification, result of "read display ID" command
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisplayId {
+    /// LCD module manufacturer ID
+    pub manufacturer: u8,
+    /// LCD module/driver version ID
+    pub version: u8,
+    /// LCD module/driver ID
+    pub driver: u8,
+}
+
+impl<'d> FsmcLcd<'d> {
+    /// Sends DCS command together with its parameters
+    pub fn write_dcs(&self, command: Command) {
+        let mut params = [0; 4];
+        let len = command.encode_params(&mut params);
+        self.write_command(command.instruction() as u16);
+        for param in &params[..len] {
+            self.write_data(u16::from(*param));
+        }
+    }
+
+    /// Sets drawing window and starts memory write
+    ///
+    /// Both corners are inclusive. Pixel data must be written with
+    /// [`FsmcLcd::write_data`] afterwards, row by row.
+    pub fn write_window(&self, x0: u16, y0: u16, x1: u16, y1: u16) {
+        self.write_dcs(Command::SetColumnAddress { start: x0, end: x1 });
+        self.write_dcs(Command::SetPageAddress { start: y0, end: y1 });
+        self.write_dcs(Command::WriteMemoryStart);
+    }
+
+    /// Sends DCS read command and reads its response into `buf`
+    ///
+    /// The dummy read required by the 8080 read protocol is done
+    /// automatically. Only lower 8 bits of each read word are used, as DCS
+    /// parameters are always 8 bits wide.
+    pub fn read_dcs(&self, instruction: Instruction, buf: &mut [u8]) {
+        self.read_register(instruction as u8, buf);
+    }
+
+    /// Sends read command and reads 8-bit response, skipping dummy read
+    pub(crate) fn read_register(&self, cmd: u8, buf: &mut [u8]) {
+        self.write_command(u16::from(cmd));
+        // Dummy read
+        self.read_data();
+        for byte in buf {
+            *byte = self.read_data() as u8;
+        }
+    }
+
+    /// Reads display identification
+    pub fn read_display_id(&self) -> DisplayId {
+        let mut buf = [0; 3];
+        self.read_dcs(Instruction::ReadDisplayId, &mut buf);
+        DisplayId {
+            manufacturer: buf[0],
+            version: buf[1],
+            driver: buf[2],
+        }
+    }
+
+    /// Reads display power mode
+    pub fn read_power_mode(&self) -> PowerMode {
+        let mut buf = [0];
+        self.read_dcs(Instruction::ReadPowerMode, &mut buf);
+        PowerMode::from_bits(buf[0])
+    }
+
+    /// Reads current memory access mode
+    pub fn read_address_mode(&self) -> AddressMode {
+        let mut buf = [0];
+        self.read_dcs(Instruction::ReadAddressMode, &mut buf);
+        AddressMode::from_bits(buf[0])
+    }
+
+    /// Reads current pixel format, `None` if controller reports unknown one
+    pub fn read_pixel_format(&self) -> Option<PixelFormat> {
+        let mut buf = [0];
+        self.read_dcs(Instruction::ReadPixelFormat, &mut buf);
+        PixelFormat::from_bits(buf[0])
+    }
+}
@@ -0,0 +1,185 @@
+//! Bus integrity diagnostics
+//!
+//! Single broken or shorted data line usually shows up just as wrong
+//! colors. [`FsmcLcd::test_bus`] writes walking-one and walking-zero
+//! patterns into display memory, reads them back and reports which data
+//! lines misbehave.
+//!
+//! Test must be run after controller is initialized with 16-bit pixel
+//! format. It overwrites first 64 pixels of the top row.
+//!
+//! Write path is checked for all data lines. On read path controllers
+//! return 6-bit color components aligned to the top of each byte, two bytes
+//! per bus word, so D0, D1, D8 and D9 carry no data there and are not
+//! checked. Each pattern is written to two neighbouring pixels, whose
+//! components are read back on opposite halves of the bus, so read path
+//! faults corrupt only one copy and are told apart from write path ones.
+//! Faults hitting both halves the same way, e.g. D15 shorted to D7, are
+//! reported as write path faults.
+use display_interface::DisplayError;
+
+use crate::dcs::{AddressMode, Command, Instruction};
+use crate::readback::rgb565_from_components;
+use crate::{FsmcLcd, Rect};
+
+/// Number of test patterns: walking one and walking zero for each line
+const PATTERNS: usize = 32;
+
+/// Number of bus words read back, two pixels per pattern take three words
+const WORDS: usize = PATTERNS * 3;
+
+/// Bits of read words carrying pixel data
+///
+/// Words hold red and green, blue and red, green and blue bytes of two
+/// pixels. Red and blue have 5 significant bits, green 6.
+const READ_BITS: [u16; 3] = [0xF8FC, 0xF8F8, 0xFCF8];
+
+/// Returns bytes of color components of RGB565 pixel as controller returns
+/// them
+fn components(pixel: u16) -> [u16; 3] {
+    [(pixel >> 11) << 3, ((pixel >> 5) & 0x3F) << 2, (pixel & 0x1F) << 3]
+}
+
+/// Returns bus words read back for two pixels with given value
+fn read_words(pixel: u16) -> [u16; 3] {
+    let [r, g, b] = components(pixel);
+    [r << 8 | g, b << 8 | r, g << 8 | b]
+}
+
+/// Returns two pixels decoded from three read bus words
+fn decode_pair(words: &[u16]) -> [u16; 2] {
+    let [r0, g0] = words[0].to_be_bytes();
+    let [b0, r1] = words[1].to_be_bytes();
+    let [g1, b1] = words[2].to_be_bytes();
+    [rgb565_from_components(r0, g0, b0), rgb565_from_components(r1, g1, b1)]
+}
+
+/// Result of data bus test
+///
+/// Each mask has bit N set if data line DN has corresponding problem.
+/// `stuck_high`, `stuck_low`, `shorted` and `swapped` describe write path.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BusTestReport {
+    /// Lines that never read as zero
+    pub stuck_high: u16,
+    /// Lines that never read as one
+    pub stuck_low: u16,
+    /// Lines that follow other lines
+    pub shorted: u16,
+    /// Lines whose value shows up on another single line
+    pub swapped: u16,
+    /// Lines returning wrong values on read path
+    pub read_errors: u16,
+    /// Whether command/data selection (RS) and chip select (CS) work, checked
+    /// by writing and reading back address mode register
+    pub control_ok: bool,
+}
+
+impl BusTestReport {
+    /// Whether no problems were found
+    pub fn is_ok(&self) -> bool {
+        self.control_ok
+            && self.stuck_high == 0
+            && self.stuck_low == 0
+            && self.shorted == 0
+            && self.swapped == 0
+            && self.read_errors == 0
+    }
+
+    /// Analyzes bus words read back after writing each test pattern to two
+    /// pixels
+    fn from_readings(
+        patterns: &[u16; PATTERNS],
+        words: &[u16; WORDS],
+        control_ok: bool,
+    ) -> Self {
+        // Copies disagree where a read line failed, pattern is what was
+        // written then
+        let mut readings = [0u16; PATTERNS];
+        let mut read_errors = 0;
+        for ((reading, pattern), words) in
+            readings.iter_mut().zip(patterns).zip(words.chunks_exact(3))
+        {
+            let [even, odd] = decode_pair(words);
+            let agree = !(even ^ odd);
+            *reading = (even & agree) | (pattern & !agree);
+            for ((word, expected), bits) in words.iter().zip(read_words(*reading)).zip(READ_BITS) {
+                read_errors |= (word ^ expected) & bits;
+            }
+        }
+
+        let (ever_one, ever_zero) = readings
+            .iter()
+            .fold((0, 0), |(one, zero), value| (one | value, zero | !value));
+        let stuck_high = !ever_zero;
+        let stuck_low = !ever_one;
+        let stuck = stuck_high | stuck_low;
+
+        let mut shorted = 0;
+        let mut swapped = 0;
+        for line in 0..16 {
+            let bit = 1u16 << line;
+            if stuck & bit != 0 {
+                continue;
+            }
+
+            let one = readings[line];
+            let zero = !readings[16 + line];
+            if one != bit && one.count_ones() == 1 && one == zero && one & stuck == 0 {
+                swapped |= bit;
+                continue;
+            }
+
+            // Lines pulled along with tested one
+            let followers = ((one | zero) & !bit) & !stuck;
+            if followers != 0 {
+                shorted |= followers | bit;
+            }
+        }
+
+        Self { stuck_high, stuck_low, shorted, swapped, read_errors, control_ok }
+    }
+}
+
+impl<'d> FsmcLcd<'d> {
+    /// Checks that address mode register can be written and read back
+    fn test_control_lines(&self) -> bool {
+        let original = self.read_address_mode();
+        let ok = [0xA8, 0x54].iter().all(|&bits| {
+            self.write_dcs(Command::SetAddressMode(AddressMode::from_bits(bits)));
+            self.read_address_mode().bits() == bits
+        });
+        self.write_dcs(Command::SetAddressMode(original));
+        ok
+    }
+
+    /// Tests data bus integrity
+    ///
+    /// See [module documentation](crate::diag) for details. Returns
+    /// [`DisplayError::OutOfBoundsError`] if panel is too narrow for the test.
+    pub fn test_bus(&self) -> Result<BusTestReport, DisplayError> {
+        let control_ok = self.test_control_lines();
+
+        let patterns: [u16; PATTERNS] =
+            core::array::from_fn(|n| if n < 16 { 1 << n } else { !(1 << (n - 16)) });
+        let rect = Rect::new(0, 0, 2 * PATTERNS as u32, 1);
+        if rect.intersection(&self.bounds()) != Some(rect) {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+
+        self.write_window(0, 0, 2 * PATTERNS as u16 - 1, 0);
+        for pattern in patterns {
+            self.write_data(pattern);
+            self.write_data(pattern);
+        }
+
+        // Raw words, conversion to RGB565 would mix lines of both halves
+        self.set_read_window(&rect)?;
+        self.write_command(Instruction::ReadMemoryStart as u16);
+        // Dummy read
+        self.read_data();
+        let words: [u16; WORDS] = core::array::from_fn(|_| self.read_data());
+
+        Ok(BusTestReport::from_readings(&patterns, &words, control_ok))
+    }
+}
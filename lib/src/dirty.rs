@@ -0,0 +1,217 @@
+//! Frame buffer with dirty rectangle tracking
+//!
+//! [`DirtyFrame`] remembers which regions of the frame were changed since
+//! the last flush and sends only them to display, each one as a separate
+//! window. Overlapping regions are merged. When all `N` regions are taken,
+//! new region is merged with the one which adds the fewest pixels, so at
+//! most `N` windows are written on flush.
+//!
+//! With `graphics` feature [`DirtyFrame`] implements embedded-graphics
+//! `DrawTarget`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embassy_stm32_fsmc_display_interface::{FsmcLcd, Rect};
+//! use embassy_stm32_fsmc_display_interface::dirty::DirtyFrame;
+//! use embassy_stm32_fsmc_display_interface::double_buffer::Frame;
+//! # fn run(lcd: &FsmcLcd<'_>, frame: &mut Frame<320, 240>) {
+//!
+//! let mut frame: DirtyFrame<320, 240, 8> = DirtyFrame::new(frame);
+//! frame.flush(lcd).unwrap();
+//!
+//! // Only 40x16 window is sent to display
+//! frame.fill_rect(Rect::new(270, 4, 40, 16), 0xFFFF);
+//! frame.flush(lcd).unwrap();
+//! # }
+//! ```
+
+use display_interface::DisplayError;
+
+use crate::double_buffer::Frame;
+use crate::{FsmcLcd, Rect};
+
+/// Frame buffer which flushes only changed regions
+pub struct DirtyFrame<'a, const W: usize, const H: usize, const N: usize> {
+    frame: &'a mut Frame<W, H>,
+    dirty: [Rect; N],
+    dirty_count: usize,
+}
+
+impl<'a, const W: usize, const H: usize, const N: usize> DirtyFrame<'a, W, H, N> {
+    /// Wraps frame buffer, tracking up to `N` dirty regions
+    ///
+    /// Whole frame is initially marked as dirty, so the first flush shows
+    /// it completely.
+    pub fn new(frame: &'a mut Frame<W, H>) -> Self {
+        const { assert!(N > 0, "at least one dirty region must be tracked") };
+        let mut this = Self {
+            frame,
+            dirty: [Rect::default(); N],
+            dirty_count: 0,
+        };
+        this.mark_dirty(this.bounds());
+        this
+    }
+
+    /// Returns rectangle covering the whole frame
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0, 0, W as u32, H as u32)
+    }
+
+    /// Returns frame buffer
+    pub fn frame(&self) -> &Frame<W, H> {
+        &*self.frame
+    }
+
+    /// Returns frame buffer for modification
+    ///
+    /// Changed regions must be marked with [`DirtyFrame::mark_dirty`].
+    pub fn frame_mut(&mut self) -> &mut Frame<W, H> {
+        &mut *self.frame
+    }
+
+    /// Returns regions changed since the last flush
+    pub fn dirty_regions(&self) -> &[Rect] {
+        &self.dirty[..self.dirty_count]
+    }
+
+    /// Whether anything was changed since the last flush
+    pub fn is_dirty(&self) -> bool {
+        self.dirty_count > 0
+    }
+
+    /// Removes dirty region by index
+    fn take_region(&mut self, index: usize) -> Rect {
+        let rect = self.dirty[index];
+        self.dirty_count -= 1;
+        self.dirty[index] = self.dirty[self.dirty_count];
+        rect
+    }
+
+    /// Marks region as changed
+    pub fn mark_dirty(&mut self, rect: Rect) {
+        let Some(mut rect) = rect.intersection(&self.bounds()) else {
+            return;
+        };
+        loop {
+            let overlapping = self
+                .dirty_regions()
+                .iter()
+                .position(|dirty| dirty.intersection(&rect).is_some());
+            if let Some(index) = overlapping {
+                rect = rect.union(&self.take_region(index));
+                continue;
+            }
+            if self.dirty_count < N {
+                self.dirty[self.dirty_count] = rect;
+                self.dirty_count += 1;
+                return;
+            }
+            // Merged region may overlap others, so check again
+            let cheapest = self
+                .dirty_regions()
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, dirty)| dirty.union(&rect).area() - dirty.area())
+                .map(|(index, _)| index)
+                .unwrap_or_default();
+            rect = rect.union(&self.take_region(cheapest));
+        }
+    }
+
+    /// Sets single pixel, pixels outside of the frame are ignored
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: u16) {
+        if let (Ok(col), Ok(row)) = (usize::try_from(x), usize::try_from(y))
+            && col < W
+            && row < H
+        {
+            self.frame.pixels[row][col] = color;
+            self.mark_dirty(Rect::new(x, y, 1, 1));
+        }
+    }
+
+    /// Fills rectangle with single color, clipping it to the frame
+    pub fn fill_rect(&mut self, rect: Rect, color: u16) {
+        let Some(rect) = rect.intersection(&self.bounds()) else {
+            return;
+        };
+        let (x, width) = (rect.x as usize, rect.width as usize);
+        for row in self.frame.pixels[rect.y as usize..].iter_mut().take(rect.height as usize) {
+            row[x..x + width].fill(color);
+        }
+        self.mark_dirty(rect);
+    }
+
+    /// Sends changed regions to display and clears them
+    ///
+    /// Frame is shown at the top left corner of the display.
+    pub fn flush(&mut self, lcd: &FsmcLcd<'_>) -> Result<(), DisplayError> {
+        let pixels = self.frame.as_slice();
+        for rect in &self.dirty[..self.dirty_count] {
+            let offset = rect.y as usize * W + rect.x as usize;
+            lcd.blit(*rect, &pixels[offset..], W)?;
+        }
+        self.dirty_count = 0;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+mod graphics {
+    use core::convert::Infallible;
+
+    use embedded_graphics_core::Pixel;
+    use embedded_graphics_core::draw_target::DrawTarget;
+    use embedded_graphics_core::geometry::{OriginDimensions, Size};
+    use embedded_graphics_core::pixelcolor::{IntoStorage, Rgb565};
+    use embedded_graphics_core::primitives::Rectangle;
+
+    use super::DirtyFrame;
+    use crate::Rect;
+
+    fn to_rect(area: &Rectangle) -> Rect {
+        Rect::new(area.top_left.x, area.top_left.y, area.size.width, area.size.height)
+    }
+
+    impl<const W: usize, const H: usize, const N: usize> OriginDimensions
+        for DirtyFrame<'_, W, H, N>
+    {
+        fn size(&self) -> Size {
+            Size::new(W as u32, H as u32)
+        }
+    }
+
+    impl<const W: usize, const H: usize, const N: usize> DrawTarget for DirtyFrame<'_, W, H, N> {
+        type Color = Rgb565;
+        type Error = Infallible;
+
+        /// Marks bounding box of all drawn pixels as dirty
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            let bounds = self.bounds();
+            let mut changed = Rect::default();
+            for Pixel(point, color) in pixels {
+                let pixel = Rect::new(point.x, point.y, 1, 1);
+                if pixel.intersection(&bounds).is_some() {
+                    self.frame.pixels[point.y as usize][point.x as usize] = color.into_storage();
+                    changed = changed.union(&pixel);
+                }
+            }
+            self.mark_dirty(changed);
+            Ok(())
+        }
+
+        fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+            self.fill_rect(to_rect(area), color.into_storage());
+            Ok(())
+        }
+
+        fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+            self.fill_rect(self.bounds(), color.into_storage());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! embedded-graphics drawing directly to DCS-compatible controller
+//!
+//! [`DcsDisplay`] implements `DrawTarget` on top of [`FsmcLcd`] without a
+//! display driver in between. Filled rectangles and images are written as
+//! single window each, solid fills use [`FsmcLcd::fill_data`], so drawing
+//! speed is limited by the bus.
+//!
+//! Controller must be initialized with 16-bit pixel format before drawing,
+//! e.g. with [`FsmcLcd::run_init`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embassy_stm32_fsmc_display_interface::FsmcLcd;
+//! use embassy_stm32_fsmc_display_interface::display::{DcsController, DcsDisplay};
+//! use embedded_graphics_core::draw_target::DrawTarget;
+//! use embedded_graphics_core::pixelcolor::{Rgb565, RgbColor};
+//! # fn run(lcd: FsmcLcd<'_>) {
+//!
+//! let mut display = DcsDisplay::new(lcd, DcsController::ILI9341.swap_xy());
+//! display.clear(Rgb565::BLUE).unwrap();
+//! # }
+//! ```
+
+use core::convert::Infallible;
+
+use embedded_graphics_core::Pixel;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Dimensions, OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::{IntoStorage, Rgb565};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::{FsmcLcd, Rect};
+
+/// Description of DCS-compatible controller and attached panel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DcsController {
+    /// Visible width in current orientation
+    pub width: u16,
+    /// Visible height in current orientation
+    pub height: u16,
+    /// Column of controller memory where visible area starts
+    pub offset_x: u16,
+    /// Row of controller memory where visible area starts
+    pub offset_y: u16,
+}
+
+impl DcsController {
+    /// ILI9341, 240x320
+    pub const ILI9341: Self = Self::new(240, 320);
+    /// ILI9486, 320x480
+    pub const ILI9486: Self = Self::new(320, 480);
+    /// ST7789V with 240x320 panel
+    pub const ST7789V: Self = Self::new(240, 320);
+    /// HX8357D, 320x480
+    pub const HX8357D: Self = Self::new(320, 480);
+
+    /// Creates description of controller with given visible size and no
+    /// offset
+    pub const fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            offset_x: 0,
+            offset_y: 0,
+        }
+    }
+
+    /// Sets offset of visible area in controller memory
+    ///
+    /// Some panels, e.g. 240x240 ones driven by ST7789, don't start at the
+    /// first column or row.
+    pub const fn offset(mut self, offset_x: u16, offset_y: u16) -> Self {
+        self.offset_x = offset_x;
+        self.offset_y = offset_y;
+        self
+    }
+
+    /// Swaps width with height and horizontal offset with vertical one,
+    /// matching address mode with row/column exchange
+    pub const fn swap_xy(self) -> Self {
+        Self {
+            width: self.height,
+            height: self.width,
+            offset_x: self.offset_y,
+            offset_y: self.offset_x,
+        }
+    }
+}
+
+/// Display drawing directly through [`FsmcLcd`]
+pub struct DcsDisplay<'d> {
+    lcd: FsmcLcd<'d>,
+    controller: DcsController,
+}
+
+impl<'d> DcsDisplay<'d> {
+    /// Creates display, panel size of `lcd` is set to controller size
+    pub fn new(mut lcd: FsmcLcd<'d>, controller: DcsController) -> Self {
+        lcd.set_panel_size(controller.width, controller.height);
+        Self { lcd, controller }
+    }
+
+    /// Returns controller description
+    pub fn controller(&self) -> &DcsController {
+        &self.controller
+    }
+
+    /// Returns underlying interface
+    pub fn lcd(&self) -> &FsmcLcd<'d> {
+        &self.lcd
+    }
+
+    /// Returns underlying interface for modification
+    pub fn lcd_mut(&mut self) -> &mut FsmcLcd<'d> {
+        &mut self.lcd
+    }
+
+    /// Returns underlying interface
+    pub fn release(self) -> FsmcLcd<'d> {
+        self.lcd
+    }
+
+    /// Sets drawing window covering rectangle, which must be inside of the
+    /// panel
+    fn set_window(&self, rect: &Rect) {
+        let x = rect.x as u16 + self.controller.offset_x;
+        let y = rect.y as u16 + self.controller.offset_y;
+        self.lcd.write_window(x, y, x + rect.width as u16 - 1, y + rect.height as u16 - 1);
+    }
+
+    /// Clips area to panel
+    fn clip(&self, area: &Rectangle) -> Option<Rect> {
+        let rect = Rect::new(area.top_left.x, area.top_left.y, area.size.width, area.size.height);
+        rect.intersection(&self.lcd.bounds())
+    }
+}
+
+impl<'d> OriginDimensions for DcsDisplay<'d> {
+    fn size(&self) -> Size {
+        Size::new(u32::from(self.controller.width), u32::from(self.controller.height))
+    }
+}
+
+impl<'d> DrawTarget for DcsDisplay<'d> {
+    type Color = Rgb565;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.lcd.bounds();
+        for Pixel(point, color) in pixels {
+            let pixel = Rect::new(point.x, point.y, 1, 1);
+            if pixel.intersection(&bounds).is_some() {
+                self.set_window(&pixel);
+                self.lcd.write_data(color.into_storage());
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let Some(clipped) = self.clip(area) else {
+            return Ok(());
+        };
+        self.set_window(&clipped);
+
+        if clipped.width == area.size.width && clipped.height == area.size.height {
+            for color in colors.into_iter().take(clipped.area() as usize) {
+                self.lcd.write_data(color.into_storage());
+            }
+            return Ok(());
+        }
+
+        // Skip colors of pixels outside of the panel
+        let width = area.size.width as usize;
+        let dx = (clipped.x - area.top_left.x) as usize;
+        let dy = (clipped.y - area.top_left.y) as usize;
+        let columns = dx..dx + clipped.width as usize;
+        let mut colors = colors.into_iter().skip(dy * width);
+        for _ in 0..clipped.height {
+            for (column, color) in colors.by_ref().take(width).enumerate() {
+                if columns.contains(&column) {
+                    self.lcd.write_data(color.into_storage());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let Some(clipped) = self.clip(area) else {
+            return Ok(());
+        };
+        self.set_window(&clipped);
+        self.lcd.fill_data(color.into_storage(), clipped.area() as u32);
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_solid(&self.bounding_box(), color)
+    }
+}
@@ -0,0 +1,285 @@
+//! DMA transfers to display data register
+//!
+//! embassy-stm32 DMA driver supports only transfers between memory and
+//! peripherals, while FSMC is mapped into memory address space. [`FsmcDma`]
+//! programs DMA2 stream directly in memory-to-memory mode, writing pixels
+//! from a buffer into FSMC data address, or into address of a [`DataPort`]
+//! with [`FsmcDma::write_to`]. Only DMA2 is able to do memory-to-memory
+//! transfers.
+//!
+//! Transfer completion is polled: waiting task wakes itself until transfer
+//! is done, so executor keeps polling and CPU doesn't sleep while transfer
+//! is awaited. embassy-stm32 defines DMA interrupt handlers itself, so
+//! transfer complete interrupt can't be used to wake the task. Buffers must
+//! be in memory accessible by DMA, which excludes CCM RAM, transfer error
+//! is reported as [`DisplayError::BusWriteError`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embassy_stm32_fsmc_display_interface::FsmcLcd;
+//! use embassy_stm32_fsmc_display_interface::dma::FsmcDma;
+//! # async fn run(lcd: &FsmcLcd<'_>, p: embassy_stm32::Peripherals) {
+//!
+//! let mut dma = FsmcDma::new(p.DMA2_CH0);
+//! let pixels = [0xF800u16; 320];
+//! lcd.write_window(0, 0, 319, 0);
+//! dma.write(&pixels).await.unwrap();
+//! # }
+//! ```
+
+use core::future::poll_fn;
+use core::task::Poll;
+
+use display_interface::DisplayError;
+use embassy_stm32::Peri;
+use embassy_stm32::dma::{AnyChannel, Channel};
+use embassy_stm32::pac::DMA2;
+use embassy_stm32::pac::dma::vals::{Burst, Dir, Dmdis, Fth, Pl, Size};
+use embassy_stm32::peripherals;
+
+use crate::{DATA_ADDRESS, DataPort};
+
+/// Maximum number of items in single DMA transfer
+const MAX_ITEMS: usize = u16::MAX as usize;
+
+/// CCM RAM address range, not accessible by DMA
+const CCM_RAM: core::ops::Range<usize> = 0x1000_0000..0x1001_0000;
+
+mod sealed {
+    pub trait Sealed {
+        /// Stream number within DMA2
+        const STREAM: usize;
+    }
+}
+
+/// DMA channel which can be used for transfers to FSMC, that is any DMA2
+/// stream
+pub trait FsmcDmaChannel: Channel + sealed::Sealed {}
+
+macro_rules! impl_fsmc_dma_channel {
+    ($($channel:ident => $stream:expr),* $(,)?) => {
+        $(
+            impl sealed::Sealed for peripherals::$channel {
+                const STREAM: usize = $stream;
+            }
+            impl FsmcDmaChannel for peripherals::$channel {}
+        )*
+    };
+}
+
+impl_fsmc_dma_channel!(
+    DMA2_CH0 => 0,
+    DMA2_CH1 => 1,
+    DMA2_CH2 => 2,
+    DMA2_CH3 => 3,
+    DMA2_CH4 => 4,
+    DMA2_CH5 => 5,
+    DMA2_CH6 => 6,
+    DMA2_CH7 => 7,
+);
+
+/// DMA stream writing pixels to FSMC data address
+///
+/// Bus is not locked during transfer: nothing else must access display
+/// while transfer is in progress. Dropping the stream aborts transfer.
+pub struct FsmcDma<'d> {
+    _channel: Peri<'d, AnyChannel>,
+    stream: usize,
+    interrupt: bool,
+}
+
+impl<'d> FsmcDma<'d> {
+    /// Takes ownership of DMA2 stream
+    pub fn new<C: FsmcDmaChannel>(channel: Peri<'d, C>) -> Self {
+        Self {
+            _channel: channel.into(),
+            stream: C::STREAM,
+            interrupt: false,
+        }
+    }
+
+    /// Sets whether transfer complete interrupt of the stream is enabled
+    ///
+    /// Error interrupt is never enabled, since embassy-stm32 handler panics
+    /// on transfer errors.
+    #[cfg(feature = "lvgl")]
+    pub(crate) fn set_interrupt(&mut self, enabled: bool) {
+        self.interrupt = enabled;
+    }
+
+    /// Whether transfer is in progress
+    pub fn is_busy(&self) -> bool {
+        DMA2.st(self.stream).cr().read().en()
+    }
+
+    /// Starts transfer of the beginning of `pixels` to `target` address and
+    /// returns number of pixels being transferred
+    ///
+    /// Word-aligned buffers are read by words, so up to 131070 pixels are
+    /// transferred at once, otherwise up to 65535. Previous transfer must
+    /// be finished. Returns [`DisplayError::BusWriteError`] if `pixels` are
+    /// in CCM RAM.
+    ///
+    /// # Safety
+    ///
+    /// `pixels` must stay valid until transfer is finished or aborted,
+    /// `target` must be display data address.
+    pub(crate) unsafe fn start(
+        &mut self,
+        target: *mut u16,
+        pixels: &[u16],
+    ) -> Result<usize, DisplayError> {
+        debug_assert!(!self.is_busy());
+        if CCM_RAM.contains(&(pixels.as_ptr() as usize)) {
+            return Err(DisplayError::BusWriteError);
+        }
+        let word_aligned = (pixels.as_ptr() as usize).is_multiple_of(4) && pixels.len() >= 2;
+        let (source_size, items, count) = if word_aligned {
+            let items = (pixels.len() / 2).min(MAX_ITEMS);
+            (Size::BITS32, items, items * 2)
+        } else {
+            let items = pixels.len().min(MAX_ITEMS);
+            (Size::BITS16, items, items)
+        };
+        if count == 0 {
+            return Ok(0);
+        }
+
+        self.clear_flags();
+        let st = DMA2.st(self.stream);
+        // In memory-to-memory mode peripheral port is the source
+        st.par().write_value(pixels.as_ptr() as u32);
+        st.m0ar().write_value(target as u32);
+        st.ndtr().write(|w| w.set_ndt(items as u16));
+        // Direct mode is not allowed in memory-to-memory mode, FIFO also
+        // splits words into halfwords
+        st.fcr().write(|w| {
+            w.set_dmdis(Dmdis::DISABLED);
+            w.set_fth(Fth::HALF);
+        });
+        st.cr().write(|w| {
+            w.set_dir(Dir::MEMORY_TO_MEMORY);
+            w.set_pinc(true);
+            w.set_minc(false);
+            w.set_psize(source_size);
+            w.set_msize(Size::BITS16);
+            w.set_pburst(Burst::SINGLE);
+            w.set_mburst(Burst::SINGLE);
+            w.set_pl(Pl::HIGH);
+            w.set_tcie(self.interrupt);
+        });
+        st.cr().modify(|w| w.set_en(true));
+        Ok(count)
+    }
+
+    /// Clears stream interrupt flags, returns whether transfer error occurred
+    fn clear_flags(&self) -> bool {
+        let (register, bit) = (self.stream / 4, self.stream % 4);
+        let error = DMA2.isr(register).read().teif(bit);
+        DMA2.ifcr(register).write(|w| {
+            w.set_tcif(bit, true);
+            w.set_htif(bit, true);
+            w.set_teif(bit, true);
+            w.set_dmeif(bit, true);
+            w.set_feif(bit, true);
+        });
+        error
+    }
+
+    /// Clears stream interrupt flags, reporting transfer error
+    fn finish(&self) -> Result<(), DisplayError> {
+        if self.clear_flags() {
+            return Err(DisplayError::BusWriteError);
+        }
+        Ok(())
+    }
+
+    /// Waits for current transfer to finish
+    ///
+    /// Returns [`DisplayError::BusWriteError`] if transfer failed.
+    pub async fn wait(&mut self) -> Result<(), DisplayError> {
+        poll_fn(|cx| {
+            if self.is_busy() {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+        .await;
+        self.finish()
+    }
+
+    /// Waits for current transfer to finish, blocking
+    pub fn wait_blocking(&mut self) -> Result<(), DisplayError> {
+        while self.is_busy() {}
+        self.finish()
+    }
+
+    /// Stops current transfer
+    pub fn abort(&mut self) {
+        DMA2.st(self.stream).cr().modify(|w| w.set_en(false));
+        while self.is_busy() {}
+        self.clear_flags();
+    }
+
+    /// Writes pixels to display data address
+    ///
+    /// Display window must be set up before, see
+    /// [`FsmcLcd::write_window`](crate::FsmcLcd::write_window).
+    pub async fn write(&mut self, pixels: &[u16]) -> Result<(), DisplayError> {
+        // SAFETY: data address of the bank
+        unsafe { self.write_address(DATA_ADDRESS as *mut u16, pixels).await }
+    }
+
+    /// Writes pixels to data address of `port`
+    ///
+    /// Same as [`FsmcDma::write`], but e.g. for display selected by extra
+    /// address lines, see [`select`](crate::select).
+    pub async fn write_to(
+        &mut self,
+        port: &DataPort<'_>,
+        pixels: &[u16],
+    ) -> Result<(), DisplayError> {
+        // SAFETY: port address is display data address
+        unsafe { self.write_address(port.address(), pixels).await }
+    }
+
+    /// Writes pixels to `target` address
+    ///
+    /// # Safety
+    ///
+    /// `target` must be display data address.
+    async unsafe fn write_address(
+        &mut self,
+        target: *mut u16,
+        pixels: &[u16],
+    ) -> Result<(), DisplayError> {
+        let guard = AbortOnDrop(self);
+        let mut rest = pixels;
+        while !rest.is_empty() {
+            // SAFETY: transfer is awaited before `pixels` borrow ends, guard
+            // aborts it if future is cancelled
+            let count = unsafe { guard.0.start(target, rest)? };
+            guard.0.wait().await?;
+            rest = &rest[count..];
+        }
+        Ok(())
+    }
+}
+
+/// Aborts transfer when dropped
+pub(crate) struct AbortOnDrop<'a, 'd>(pub(crate) &'a mut FsmcDma<'d>);
+
+impl Drop for AbortOnDrop<'_, '_> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl<'d> Drop for FsmcDma<'d> {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
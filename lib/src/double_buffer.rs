@@ -0,0 +1,284 @@
+//! Double-buffered frame presentation
+//!
+//! [`DoubleBuffer`] keeps two frame buffers: while one of them is streamed
+//! to display by DMA, next frame is rendered into another one.
+//! [`DoubleBuffer::present`] waits until previous frame is flushed, swaps
+//! buffers and starts flushing just rendered frame in background.
+//!
+//! Frames are written to the top left corner of display, so frame size
+//! usually matches panel size. Buffers may be placed into internal RAM or
+//! into external SRAM connected to another FSMC bank, but not into CCM RAM.
+//!
+//! Single DMA transfer covers up to 131070 pixels, which is enough for
+//! 320x240 and 480x272 panels. Larger frames are flushed in parts, and next
+//! part is started only while [`DoubleBuffer::present`] or
+//! [`DoubleBuffer::flush`] is awaited.
+//!
+//! Flush runs in background only from CPU point of view: DMA completion is
+//! polled (see [`dma`](crate::dma)), so while [`DoubleBuffer::present`] or
+//! [`DoubleBuffer::flush`] is awaited, executor keeps polling the task and
+//! CPU doesn't sleep. To save power, render next frame before awaiting the
+//! previous flush, and use partial updates, e.g.
+//! [`DirtyFrame`](crate::dirty::DirtyFrame), when only small parts change.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embassy_stm32_fsmc_display_interface::FsmcLcd;
+//! use embassy_stm32_fsmc_display_interface::dma::FsmcDma;
+//! use embassy_stm32_fsmc_display_interface::double_buffer::{DoubleBuffer, Frame};
+//! # async fn run(lcd: &mut FsmcLcd<'_>, dma: FsmcDma<'_>) {
+//! # let (front, back): (&mut Frame<320, 240>, &mut Frame<320, 240>) = todo!();
+//!
+//! let mut frames = DoubleBuffer::new(lcd, dma, front, back).unwrap();
+//! loop {
+//!     frames.back().pixels[120][160] = 0xFFFF;
+//!     frames.present().await.unwrap();
+//! }
+//! # }
+//! ```
+
+use display_interface::DisplayError;
+
+use crate::FsmcLcd;
+use crate::dma::FsmcDma;
+
+/// Frame buffer of `W`x`H` RGB565 pixels
+///
+/// Buffer is word-aligned, so DMA reads it by words.
+#[repr(C, align(4))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame<const W: usize, const H: usize> {
+    /// Pixel rows
+    pub pixels: [[u16; W]; H],
+}
+
+impl<const W: usize, const H: usize> Frame<W, H> {
+    /// Creates black frame
+    pub const fn new() -> Self {
+        Self {
+            pixels: [[0; W]; H],
+        }
+    }
+
+    /// Returns all pixels, row by row
+    pub fn as_slice(&self) -> &[u16] {
+        self.pixels.as_flattened()
+    }
+
+    /// Returns all pixels, row by row
+    pub fn as_mut_slice(&mut self) -> &mut [u16] {
+        self.pixels.as_flattened_mut()
+    }
+
+    /// Fills frame with single color
+    pub fn fill(&mut self, color: u16) {
+        self.as_mut_slice().fill(color);
+    }
+}
+
+impl<const W: usize, const H: usize> Default for Frame<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pair of frame buffers flushed to display by DMA
+///
+/// Display is borrowed for the lifetime of double buffer, so nothing else
+/// can access bus while flush is in progress. Dropping double buffer aborts
+/// flush.
+pub struct DoubleBuffer<'a, 'd, const W: usize, const H: usize> {
+    lcd: &'a mut FsmcLcd<'d>,
+    dma: FsmcDma<'d>,
+    buffers: [&'a mut Frame<W, H>; 2],
+    /// Index of buffer available for rendering
+    back: usize,
+    /// Number of front buffer pixels whose transfer is already started
+    flushed: usize,
+}
+
+impl<'a, 'd, const W: usize, const H: usize> DoubleBuffer<'a, 'd, W, H> {
+    /// Creates double buffer, `back` is the first buffer to render into
+    ///
+    /// Returns [`DisplayError::OutOfBoundsError`] if frame doesn't fit panel.
+    pub fn new(
+        lcd: &'a mut FsmcLcd<'d>,
+        dma: FsmcDma<'d>,
+        front: &'a mut Frame<W, H>,
+        back: &'a mut Frame<W, H>,
+    ) -> Result<Self, DisplayError> {
+        let (width, height) = lcd.panel_size();
+        if W == 0 || H == 0 || W > usize::from(width) || H > usize::from(height) {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        Ok(Self {
+            lcd,
+            dma,
+            buffers: [front, back],
+            back: 1,
+            flushed: W * H,
+        })
+    }
+
+    /// Returns buffer to render next frame into
+    pub fn back(&mut self) -> &mut Frame<W, H> {
+        &mut *self.buffers[self.back]
+    }
+
+    /// Returns frame which is currently shown or being flushed
+    pub fn front(&self) -> &Frame<W, H> {
+        &*self.buffers[1 - self.back]
+    }
+
+    /// Whether front buffer flush is in progress
+    pub fn is_flushing(&self) -> bool {
+        self.dma.is_busy() || self.flushed < W * H
+    }
+
+    /// Starts transfer of the next part of front buffer
+    ///
+    /// Flush is abandoned on error.
+    fn flush_next(&mut self) -> Result<(), DisplayError> {
+        let front = self.buffers[1 - self.back].as_slice();
+        // SAFETY: buffer is borrowed for the lifetime of double buffer, and
+        // DMA stream aborts transfer when double buffer is dropped
+        match unsafe { self.dma.start(self.lcd.data_address(), &front[self.flushed..]) } {
+            Ok(started) => {
+                self.flushed += started;
+                Ok(())
+            }
+            Err(error) => {
+                self.flushed = W * H;
+                Err(error)
+            }
+        }
+    }
+
+    /// Waits until front buffer is completely flushed
+    ///
+    /// Returns [`DisplayError::BusWriteError`] if DMA transfer failed, rest
+    /// of the frame is not flushed then.
+    pub async fn flush(&mut self) -> Result<(), DisplayError> {
+        loop {
+            if let Err(error) = self.dma.wait().await {
+                self.flushed = W * H;
+                return Err(error);
+            }
+            if self.flushed == W * H {
+                return Ok(());
+            }
+            self.flush_next()?;
+        }
+    }
+
+    /// Presents rendered frame
+    ///
+    /// Waits until previous frame is flushed, swaps buffers and starts
+    /// flushing rendered frame in background. Afterwards [`back`] returns
+    /// previous frame, which can be redrawn.
+    ///
+    /// [`back`]: DoubleBuffer::back
+    ///
+    /// Returns error of previous frame flush, see [`DoubleBuffer::flush`],
+    /// buffers are swapped anyway.
+    pub async fn present(&mut self) -> Result<(), DisplayError> {
+        let flushed = self.flush().await;
+        self.back = 1 - self.back;
+        self.lcd.write_window(0, 0, W as u16 - 1, H as u16 - 1);
+        self.flushed = 0;
+        self.flush_next()?;
+        flushed
+    }
+
+    /// Waits for flush to finish and returns DMA stream
+    ///
+    /// Flush error is ignored, await [`DoubleBuffer::flush`] first to get it.
+    pub async fn release(mut self) -> FsmcDma<'d> {
+        let _ = self.flush().await;
+        self.dma
+    }
+}
+
+#[cfg(feature = "graphics")]
+mod graphics {
+    use core::convert::Infallible;
+
+    use embedded_graphics_core::Pixel;
+    use embedded_graphics_core::draw_target::DrawTarget;
+    use embedded_graphics_core::geometry::{Dimensions, OriginDimensions, Size};
+    use embedded_graphics_core::pixelcolor::{IntoStorage, Rgb565};
+    use embedded_graphics_core::primitives::Rectangle;
+
+    use super::{DoubleBuffer, Frame};
+
+    impl<const W: usize, const H: usize> OriginDimensions for Frame<W, H> {
+        fn size(&self) -> Size {
+            Size::new(W as u32, H as u32)
+        }
+    }
+
+    impl<const W: usize, const H: usize> DrawTarget for Frame<W, H> {
+        type Color = Rgb565;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                if let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y))
+                    && x < W
+                    && y < H
+                {
+                    self.pixels[y][x] = color.into_storage();
+                }
+            }
+            Ok(())
+        }
+
+        fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+            let area = area.intersection(&self.bounding_box());
+            let Some(bottom_right) = area.bottom_right() else {
+                return Ok(());
+            };
+            let (x0, x1) = (area.top_left.x as usize, bottom_right.x as usize);
+            for row in &mut self.pixels[area.top_left.y as usize..=bottom_right.y as usize] {
+                row[x0..=x1].fill(color.into_storage());
+            }
+            Ok(())
+        }
+
+        fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+            self.fill(color.into_storage());
+            Ok(())
+        }
+    }
+
+    impl<const W: usize, const H: usize> OriginDimensions for DoubleBuffer<'_, '_, W, H> {
+        fn size(&self) -> Size {
+            Size::new(W as u32, H as u32)
+        }
+    }
+
+    /// Draws into back buffer
+    impl<const W: usize, const H: usize> DrawTarget for DoubleBuffer<'_, '_, W, H> {
+        type Color = Rgb565;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            self.back().draw_iter(pixels)
+        }
+
+        fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+            self.back().fill_solid(area, color)
+        }
+
+        fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+            self.back().clear(color)
+        }
+    }
+}
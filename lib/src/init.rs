@@ -0,0 +1,283 @@
+//! Declarative controller initialization sequences
+//!
+//! Initialization sequence is a list of steps, each consisting of command,
+//! its parameters and a delay to wait after the command. Sequences can be
+//! declared as `const` tables with [`init_sequence!`](crate::init_sequence)
+//! or parsed from compact byte arrays used by Adafruit drivers with
+//! [`AdafruitInit`].
+//!
+//! ```
+//! use embassy_stm32_fsmc_display_interface::init::{AdafruitFormat, AdafruitInit};
+//!
+//! // SLPOUT with 150 ms delay, DISPON with 150 ms delay
+//! let init = AdafruitInit::parse(&[0x11, 0x80, 0x29, 0x80, 0x00], AdafruitFormat::Ili9341)
+//!     .unwrap();
+//! assert_eq!(init.iter().map(|step| step.delay_ms).sum::<u16>(), 300);
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embassy_stm32_fsmc_display_interface::{init_sequence, FsmcLcd};
+//! use embassy_stm32_fsmc_display_interface::init::InitStep;
+//! # fn run(lcd: &mut FsmcLcd, delay: &mut impl embedded_hal::delay::DelayNs) {
+//!
+//! const INIT: &[InitStep] = init_sequence![
+//!     (0x01, [], 150),    // Software reset
+//!     (0x3A, [0x55], 0),  // 16 bits per pixel
+//!     (0x11, [], 120),    // Exit sleep mode
+//!     (0x29, [], 0),      // Display on
+//! ];
+//!
+//! lcd.run_init(INIT.iter().copied(), delay).unwrap();
+//! # }
+//! ```
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+use crate::FsmcLcd;
+
+/// Single step of initialization sequence
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InitStep<'a> {
+    /// Command to send
+    pub cmd: u8,
+    /// Command parameters
+    pub params: &'a [u8],
+    /// Delay after command, in milliseconds
+    pub delay_ms: u16,
+}
+
+impl<'a> InitStep<'a> {
+    /// Creates a new step
+    pub const fn new(cmd: u8, params: &'a [u8], delay_ms: u16) -> Self {
+        Self { cmd, params, delay_ms }
+    }
+}
+
+/// Declares initialization sequence table
+///
+/// Each entry is `(cmd, [params...], delay_ms)`. The result is
+/// `&[InitStep]` usable in `const` context.
+///
+/// ```
+/// use embassy_stm32_fsmc_display_interface::init_sequence;
+/// use embassy_stm32_fsmc_display_interface::init::InitStep;
+///
+/// const INIT: &[InitStep] = init_sequence![
+///     (0x01, [], 150),
+///     (0x36, [0x48], 0),
+/// ];
+/// assert_eq!(INIT[1].params, &[0x48]);
+/// ```
+#[macro_export]
+macro_rules! init_sequence {
+    ($(($cmd:expr, [$($param:expr),* $(,)?], $delay:expr)),* $(,)?) => {
+        &[$($crate::init::InitStep::new($cmd, &[$($param),*], $delay)),*]
+    };
+}
+
+/// Error in compact initialization sequence
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InitError {
+    /// Sequence ends in the middle of a command
+    Truncated,
+}
+
+/// Layout of compact initialization sequence
+///
+/// All layouts encode command as command byte, followed by byte with
+/// number of parameters in low 7 bits, followed by parameters themselves.
+/// They differ in meaning of bit 7 and in how sequence ends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdafruitFormat {
+    /// Adafruit_ILI9341: bit 7 means 150 ms delay after command, sequence
+    /// is terminated with zero command byte
+    Ili9341,
+    /// Adafruit_HX8357: bit 7 means that low 7 bits are delay in 5 ms units
+    /// and command has no parameters, command 0xFF is delay only, sequence
+    /// is terminated with zero command byte
+    Hx8357,
+    /// Adafruit_ST77xx: bit 7 means that one more byte with delay in
+    /// milliseconds follows parameters, value 255 means 500 ms, sequence is
+    /// prefixed with number of commands
+    St77xx,
+}
+
+/// Initialization sequence in compact format used by Adafruit drivers
+///
+/// See [`AdafruitFormat`] for supported layouts.
+#[derive(Clone, Copy, Debug)]
+pub struct AdafruitInit<'a> {
+    data: &'a [u8],
+    format: AdafruitFormat,
+    count: Option<usize>,
+}
+
+impl<'a> AdafruitInit<'a> {
+    const DELAY_FLAG: u8 = 0x80;
+
+    /// Parses sequence of given format
+    ///
+    /// Zero-terminated sequences may also end without terminator.
+    pub fn parse(data: &'a [u8], format: AdafruitFormat) -> Result<Self, InitError> {
+        let (count, data) = match format {
+            AdafruitFormat::St77xx => {
+                let (&count, data) = data.split_first().ok_or(InitError::Truncated)?;
+                (Some(usize::from(count)), data)
+            }
+            AdafruitFormat::Ili9341 | AdafruitFormat::Hx8357 => (None, data),
+        };
+        let init = Self { data, format, count };
+        init.validate()?;
+        Ok(init)
+    }
+
+    fn validate(&self) -> Result<(), InitError> {
+        let mut iter = self.iter();
+        while iter.try_next()?.is_some() {}
+        Ok(())
+    }
+
+    /// Returns iterator over sequence steps
+    pub fn iter(&self) -> AdafruitInitIter<'a> {
+        AdafruitInitIter { data: self.data, format: self.format, remaining: self.count }
+    }
+}
+
+impl<'a> IntoIterator for AdafruitInit<'a> {
+    type Item = InitStep<'a>;
+    type IntoIter = AdafruitInitIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over steps of [`AdafruitInit`]
+#[derive(Clone, Debug)]
+pub struct AdafruitInitIter<'a> {
+    data: &'a [u8],
+    format: AdafruitFormat,
+    remaining: Option<usize>,
+}
+
+impl<'a> AdafruitInitIter<'a> {
+    /// HX8357 command which only waits
+    const HX8357_DELAY: u8 = 0xFF;
+    /// Command sent for HX8357 delays at the start of sequence (NOP)
+    const NOP: u8 = 0x00;
+
+    fn try_next(&mut self) -> Result<Option<InitStep<'a>>, InitError> {
+        let Some(mut step) = self.try_next_entry()? else {
+            return Ok(None);
+        };
+        if self.format == AdafruitFormat::Hx8357 {
+            // Delay-only entries are merged into preceding command
+            while self.data.first() == Some(&Self::HX8357_DELAY) {
+                let Some(delay) = self.try_next_entry()? else {
+                    break;
+                };
+                step.delay_ms = step.delay_ms.saturating_add(delay.delay_ms);
+            }
+        }
+        Ok(Some(step))
+    }
+
+    fn try_next_entry(&mut self) -> Result<Option<InitStep<'a>>, InitError> {
+        match self.remaining {
+            Some(0) => return Ok(None),
+            Some(ref mut remaining) => *remaining -= 1,
+            None => {
+                if matches!(self.data.first(), None | Some(0)) {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let [cmd, flags, rest @ ..] = self.data else {
+            return Err(InitError::Truncated);
+        };
+        let delayed = flags & AdafruitInit::DELAY_FLAG != 0;
+        let low = flags & !AdafruitInit::DELAY_FLAG;
+        let len = match self.format {
+            AdafruitFormat::Hx8357 if delayed || *cmd == Self::HX8357_DELAY => 0,
+            _ => usize::from(low),
+        };
+        if rest.len() < len {
+            return Err(InitError::Truncated);
+        }
+        let (params, mut rest) = rest.split_at(len);
+
+        let mut delay_ms = 0;
+        if delayed {
+            delay_ms = match self.format {
+                AdafruitFormat::Ili9341 => 150,
+                AdafruitFormat::Hx8357 => u16::from(low) * 5,
+                AdafruitFormat::St77xx => {
+                    let (&delay, tail) = rest.split_first().ok_or(InitError::Truncated)?;
+                    rest = tail;
+                    if delay == 255 { 500 } else { u16::from(delay) }
+                }
+            };
+        }
+
+        self.data = rest;
+        let cmd = match self.format {
+            AdafruitFormat::Hx8357 if *cmd == Self::HX8357_DELAY => Self::NOP,
+            _ => *cmd,
+        };
+        Ok(Some(InitStep::new(cmd, params, delay_ms)))
+    }
+}
+
+impl<'a> Iterator for AdafruitInitIter<'a> {
+    type Item = InitStep<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Sequence is validated on construction
+        self.try_next().ok().flatten()
+    }
+}
+
+impl<'d> FsmcLcd<'d> {
+    fn send_init_step(&mut self, step: &InitStep<'_>) -> Result<(), DisplayError> {
+        self.send_commands(DataFormat::U8(&[step.cmd]))?;
+        if !step.params.is_empty() {
+            self.send_data(DataFormat::U8(step.params))?;
+        }
+        Ok(())
+    }
+
+    /// Runs initialization sequence, using blocking delay
+    pub fn run_init<'a>(
+        &mut self,
+        steps: impl IntoIterator<Item = InitStep<'a>>,
+        delay: &mut impl embedded_hal::delay::DelayNs,
+    ) -> Result<(), DisplayError> {
+        for step in steps {
+            self.send_init_step(&step)?;
+            if step.delay_ms != 0 {
+                delay.delay_ms(u32::from(step.delay_ms));
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs initialization sequence, using async delay
+    ///
+    /// Use `embassy_time::Delay` to wait with Embassy timer.
+    pub async fn run_init_async<'a>(
+        &mut self,
+        steps: impl IntoIterator<Item = InitStep<'a>>,
+        delay: &mut impl embedded_hal_async::delay::DelayNs,
+    ) -> Result<(), DisplayError> {
+        for step in steps {
+            self.send_init_step(&step)?;
+            if step.delay_ms != 0 {
+                delay.delay_ms(u32::from(step.delay_ms)).await;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+//! Native [`lcd_async`](::lcd_async) interface
+//!
+//! Bus transfers are done by CPU, so futures complete immediately.
+
+use core::convert::Infallible;
+
+use ::lcd_async::interface::{Interface, InterfaceKind};
+
+use crate::FsmcLcd;
+
+impl<'d> Interface for FsmcLcd<'d> {
+    type Word = u16;
+    type Error = Infallible;
+
+    const KIND: InterfaceKind = InterfaceKind::Parallel16Bit;
+
+    async fn send_command(&mut self, command: u8, args: &[u8]) -> Result<(), Self::Error> {
+        self.write_command(u16::from(command));
+        for arg in args {
+            self.write_data(u16::from(*arg));
+        }
+        Ok(())
+    }
+
+    async fn send_data_slice(&mut self, data: &[Self::Word]) -> Result<(), Self::Error> {
+        for word in data {
+            self.write_data(*word);
+        }
+        Ok(())
+    }
+}
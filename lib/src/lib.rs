@@ -30,13 +30,59 @@
 //! // let display = ili9341::Ili9341::new(lcd_interface, reset_pin, ...);
 //! ```
 
+#[cfg(feature = "slint")]
+extern crate alloc;
+
 use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
 use embassy_stm32::gpio::{AfType, Flex, Pin, Speed, Pull, OutputType};
 use embassy_stm32::pac::fsmc::vals::{Accmod, Cpsize, Mtyp, Waitcfg, Waitpol};
+use embassy_stm32::pac::fsmc::regs::Btr;
 use embassy_stm32::pac::fsmc::vals::Mwid;
+use embassy_stm32::pac::fsmc::Fsmc;
 use embassy_stm32::rcc;
 use embassy_stm32::Peri;
 
+pub mod calibrate;
+pub mod dcs;
+pub mod diag;
+pub mod dirty;
+#[cfg(feature = "graphics")]
+pub mod display;
+pub mod dma;
+pub mod double_buffer;
+pub mod init;
+#[cfg(feature = "lvgl")]
+pub mod lvgl;
+pub mod probe;
+pub mod render;
+pub mod select;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod shared;
+#[cfg(feature = "slint")]
+pub mod slint;
+pub mod ssd1963;
+#[cfg(feature = "te")]
+pub mod te;
+mod blit;
+mod config;
+#[cfg(feature = "lcd-async")]
+mod lcd_async;
+#[cfg(feature = "mipidsi")]
+mod mipidsi;
+mod ports;
+mod readback;
+mod rect;
+mod regs;
+#[cfg(feature = "time")]
+mod status;
+
+pub use config::{ConfigError, FsmcLcdConfig, MemoryType, WaitPolarity};
+pub use ports::{CommandPort, DataPort};
+pub use rect::Rect;
+#[cfg(feature = "time")]
+pub use status::StatusTimeout;
+
 /// STM32F407 Reference manual, 36.5.6
 /// Register base address for FSMC
 const REG_ADDRESS: usize = 0xA000_0000;
@@ -55,6 +101,9 @@ const DATA_ADDRESS: usize = make_data_address(BASE_ADDRESS);
 /// The data address will result in all external address signals being set high.
 /// This allows the display to differentiate between command and data based on
 /// address line state (typically A18/RS pin).
+///
+/// When other address lines select displays, see [`select`] for per-display
+/// addresses.
 const fn make_data_address(base: usize) -> usize {
     // Bits 26 and 27 select the sub-bank, don't change them.
     // Bits 25 through 1 become address signals 24 through 0, set these high.
@@ -62,10 +111,48 @@ const fn make_data_address(base: usize) -> usize {
     base | 0x3fffffe
 }
 
+/// Returns FSMC registers block
+fn fsmc() -> Fsmc {
+    unsafe { Fsmc::from_ptr(REG_ADDRESS as _) }
+}
+
+/// Writes read timing register of the first bank
+fn configure_read_timing(timing: &Timing) {
+    fsmc().btr(0).write(|w| {
+        w.set_accmod(timing.access_mode);
+        w.set_busturn(timing.bus_turnaround);
+        w.set_datast(timing.data);
+        w.set_addhld(timing.address_hold);
+        w.set_addset(timing.address_setup);
+    });
+}
+
+/// Writes write timing register of the first bank
+fn configure_write_timing(timing: &Timing) {
+    fsmc().bwtr(0).write(|w| {
+        w.set_accmod(timing.access_mode);
+        w.set_busturn(timing.bus_turnaround);
+        w.set_datast(timing.data);
+        w.set_addhld(timing.address_hold);
+        w.set_addset(timing.address_setup);
+    });
+}
+
+/// Reads read timing register of the first bank
+fn current_read_timing() -> Timing {
+    Timing::from_bits(fsmc().btr(0).read().0)
+}
+
+/// Reads write timing register of the first bank
+fn current_write_timing() -> Timing {
+    Timing::from_bits(fsmc().bwtr(0).read().0)
+}
+
 /// FSMC timing configuration
 ///
 /// Controls the timing parameters for FSMC bus operations. These values
 /// determine how fast the FSMC can communicate with the display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Timing {
     /// Access mode for the memory bank
     pub access_mode: Accmod,
@@ -114,6 +201,44 @@ impl Timing {
             address_setup,
         }
     }
+
+    /// Whether all values are within allowed ranges
+    ///
+    /// Address hold phase is only used in access mode D, so its minimum is
+    /// checked for that mode only.
+    pub fn is_valid(&self) -> bool {
+        self.bus_turnaround <= Self::BUS_TURNAROUND_MAX
+            && self.data >= Self::DATA_MIN
+            && self.address_setup <= Self::ADDRESS_SETUP_MAX
+            && self.address_hold <= Self::ADDRESS_HOLD_MAX
+            && (self.access_mode != Accmod::D || self.address_hold >= Self::ADDRESS_HOLD_MIN)
+    }
+
+    /// Encodes timing in the FSMC timing register (BTR) format
+    ///
+    /// This is a compact form suitable for storing timing, e.g. results of
+    /// calibration, in flash.
+    pub fn to_bits(&self) -> u32 {
+        let mut btr = Btr(0);
+        btr.set_accmod(self.access_mode);
+        btr.set_busturn(self.bus_turnaround);
+        btr.set_datast(self.data);
+        btr.set_addhld(self.address_hold);
+        btr.set_addset(self.address_setup);
+        btr.0
+    }
+
+    /// Decodes timing from the FSMC timing register (BTR) format
+    pub fn from_bits(bits: u32) -> Self {
+        let btr = Btr(bits);
+        Self {
+            access_mode: btr.accmod(),
+            bus_turnaround: btr.busturn(),
+            data: btr.datast(),
+            address_hold: btr.addhld(),
+            address_setup: btr.addset(),
+        }
+    }
 }
 
 impl Default for Timing {
@@ -128,6 +253,14 @@ impl Default for Timing {
     }
 }
 
+/// Data bus pins D0-D15, configured for FSMC
+type DataPins<'d> = (
+    Flex<'d>, Flex<'d>, Flex<'d>, Flex<'d>,
+    Flex<'d>, Flex<'d>, Flex<'d>, Flex<'d>,
+    Flex<'d>, Flex<'d>, Flex<'d>, Flex<'d>,
+    Flex<'d>, Flex<'d>, Flex<'d>, Flex<'d>,
+);
+
 /// FSMC LCD interface for parallel displays
 ///
 /// This struct provides a DisplayInterface implementation using the STM32 FSMC
@@ -146,12 +279,13 @@ pub struct FsmcLcd<'d> {
     _rd: Flex<'d>,
     _rw: Flex<'d>,
     _rs: Flex<'d>,
-    _data_pins: (
-        Flex<'d>, Flex<'d>, Flex<'d>, Flex<'d>,
-        Flex<'d>, Flex<'d>, Flex<'d>, Flex<'d>,
-        Flex<'d>, Flex<'d>, Flex<'d>, Flex<'d>,
-        Flex<'d>, Flex<'d>, Flex<'d>, Flex<'d>,
-    ),
+    _nwait: Option<Flex<'d>>,
+    _data_pins: DataPins<'d>,
+    panel_width: u16,
+    panel_height: u16,
+    extended_mode: bool,
+    command_address: usize,
+    data_address: usize,
 }
 
 impl<'d> FsmcLcd<'d> {
@@ -185,6 +319,7 @@ impl<'d> FsmcLcd<'d> {
     ///     &Timing::default(),
     /// );
     /// ```
+    #[allow(clippy::type_complexity)]
     pub fn new(
         cs: Peri<'d, impl Pin>,
         rd: Peri<'d, impl Pin>,
@@ -198,61 +333,138 @@ impl<'d> FsmcLcd<'d> {
         ),
         read_timing: &Timing,
         write_timing: &Timing,
+    ) -> Self {
+        let config = FsmcLcdConfig::new().read_write_timing(*read_timing, *write_timing);
+        Self::new_inner(cs, rd, rw, rs, data_pins, None, &config)
+    }
+
+    /// Creates a new FSMC LCD interface with custom bank configuration
+    ///
+    /// Pins are the same as for [`FsmcLcd::new`]. Returns error if
+    /// configuration is inconsistent, see [`FsmcLcdConfig::validate`], or if
+    /// it enables NWAIT signal, use [`FsmcLcd::with_nwait`] for that.
+    #[allow(clippy::type_complexity)]
+    pub fn with_config(
+        cs: Peri<'d, impl Pin>,
+        rd: Peri<'d, impl Pin>,
+        rw: Peri<'d, impl Pin>,
+        rs: Peri<'d, impl Pin>,
+        data_pins: (
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+        ),
+        config: &FsmcLcdConfig,
+    ) -> Result<Self, ConfigError> {
+        config.validate()?;
+        if config.wait.is_some() {
+            return Err(ConfigError::MissingNwaitPin);
+        }
+        Ok(Self::new_inner(cs, rd, rw, rs, data_pins, None, config))
+    }
+
+    /// Creates a new FSMC LCD interface using NWAIT signal for flow control
+    ///
+    /// Slow controllers, such as RA8875, drive WAIT/BUSY line while they
+    /// can't accept data. With NWAIT connected to it, FSMC stalls the bus in
+    /// hardware until controller is ready, instead of relying on long data
+    /// phase.
+    ///
+    /// `nwait` is FSMC_NWAIT pin (PD6), the rest of pins are the same as for
+    /// [`FsmcLcd::new`]. NWAIT must be enabled in `config` with
+    /// [`FsmcLcdConfig::wait`], otherwise error is returned.
+    #[allow(clippy::type_complexity)]
+    pub fn with_nwait(
+        cs: Peri<'d, impl Pin>,
+        rd: Peri<'d, impl Pin>,
+        rw: Peri<'d, impl Pin>,
+        rs: Peri<'d, impl Pin>,
+        nwait: Peri<'d, impl Pin>,
+        data_pins: (
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+        ),
+        config: &FsmcLcdConfig,
+    ) -> Result<Self, ConfigError> {
+        config.validate()?;
+        // Keep NWAIT inactive while it is not driven
+        let pull = match config.wait {
+            Some(WaitPolarity::ActiveLow) => Pull::Up,
+            Some(WaitPolarity::ActiveHigh) => Pull::Down,
+            None => return Err(ConfigError::NwaitNotEnabled),
+        };
+
+        let mut nwait_flex = Flex::new(nwait);
+        nwait_flex.set_as_af_unchecked(12, AfType::input(pull));
+
+        Ok(Self::new_inner(cs, rd, rw, rs, data_pins, Some(nwait_flex), config))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn new_inner(
+        cs: Peri<'d, impl Pin>,
+        rd: Peri<'d, impl Pin>,
+        rw: Peri<'d, impl Pin>,
+        rs: Peri<'d, impl Pin>,
+        data_pins: (
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+            Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>, Peri<'d, impl Pin>,
+        ),
+        nwait: Option<Flex<'d>>,
+        config: &FsmcLcdConfig,
     ) -> Self {
         // Enable FSMC peripheral clock
         rcc::enable_and_reset::<embassy_stm32::peripherals::FSMC>();
 
-        let fsmc = unsafe { embassy_stm32::pac::fsmc::Fsmc::from_ptr(REG_ADDRESS as _) };
-
         // Configure FSMC Bank Control Register
-        fsmc.bcr(0).write(|w| {
+        fsmc().bcr(0).write(|w| {
             // Disable synchronous writes
             w.set_cburstrw(false);
             // Don't split burst transactions (doesn't matter for LCD mode)
             w.set_cpsize(Cpsize::NO_BURST_SPLIT);
-            // Ignore wait signal (asynchronous mode)
-            w.set_asyncwait(false);
-            // Enable extended mode, for different read and write timings
-            w.set_extmod(true);
+            // Use wait signal in asynchronous mode if requested
+            w.set_asyncwait(config.wait.is_some());
+            // Extended mode allows different read and write timings
+            w.set_extmod(config.write_timing.is_some());
             // Ignore wait signal (synchronous mode)
             w.set_waiten(false);
-            // Allow write operations
-            w.set_wren(true);
+            // Allow write operations if requested
+            w.set_wren(config.write_enable);
             // Default wait timing
             w.set_waitcfg(Waitcfg::BEFORE_WAIT_STATE);
-            // Default wait polarity
-            w.set_waitpol(Waitpol::ACTIVE_LOW);
+            // Wait polarity
+            w.set_waitpol(match config.wait {
+                Some(WaitPolarity::ActiveHigh) => Waitpol::ACTIVE_HIGH,
+                Some(WaitPolarity::ActiveLow) | None => Waitpol::ACTIVE_LOW,
+            });
             // Disable burst reads
             w.set_bursten(false);
-            // Enable NOR flash operations
-            w.set_faccen(true);
+            // Enable NOR flash operations for NOR memory type
+            w.set_faccen(config.memory_type == MemoryType::Nor);
             // 16-bit bus width
             w.set_mwid(Mwid::BITS16);
-            // NOR flash mode (compatible with LCD controllers)
-            w.set_mtyp(Mtyp::FLASH);
+            // NOR flash or SRAM mode (both compatible with LCD controllers)
+            w.set_mtyp(match config.memory_type {
+                MemoryType::Nor => Mtyp::FLASH,
+                MemoryType::Sram => Mtyp::SRAM,
+            });
             // Address and data not multiplexed
             w.set_muxen(false);
             // Enable this memory bank
             w.set_mbken(true);
         });
 
-        // Configure read timing
-        fsmc.btr(0).write(|w| {
-            w.set_accmod(read_timing.access_mode);
-            w.set_busturn(read_timing.bus_turnaround);
-            w.set_datast(read_timing.data);
-            w.set_addhld(read_timing.address_hold);
-            w.set_addset(read_timing.address_setup);
-        });
-
-        // Configure write timing
-        fsmc.bwtr(0).write(|w| {
-            w.set_accmod(write_timing.access_mode);
-            w.set_busturn(write_timing.bus_turnaround);
-            w.set_datast(write_timing.data);
-            w.set_addhld(write_timing.address_hold);
-            w.set_addset(write_timing.address_setup);
-        });
+        // Configure read and write timings, read timing is used for writes
+        // too if extended mode is off
+        configure_read_timing(&config.read_timing);
+        if let Some(write_timing) = &config.write_timing {
+            configure_write_timing(write_timing);
+        }
 
         // Configure all pins as FSMC alternate function (AF12)
         let af_type = AfType::output_pull(OutputType::PushPull, Speed::VeryHigh, Pull::None);
@@ -304,24 +516,120 @@ impl<'d> FsmcLcd<'d> {
             _rd: rd_flex,
             _rw: rw_flex,
             _rs: rs_flex,
+            _nwait: nwait,
             _data_pins: (
                 d0_flex, d1_flex, d2_flex, d3_flex,
                 d4_flex, d5_flex, d6_flex, d7_flex,
                 d8_flex, d9_flex, d10_flex, d11_flex,
                 d12_flex, d13_flex, d14_flex, d15_flex,
             ),
+            panel_width: u16::MAX,
+            panel_height: u16::MAX,
+            extended_mode: config.write_timing.is_some(),
+            command_address: COMMAND_ADDRESS,
+            data_address: DATA_ADDRESS,
+        }
+    }
+
+    /// Sets size of the connected panel, in current orientation
+    ///
+    /// It is used for clipping drawing operations. By default size is not
+    /// known and only 16-bit coordinate range limits drawing.
+    pub fn set_panel_size(&mut self, width: u16, height: u16) {
+        self.panel_width = width;
+        self.panel_height = height;
+    }
+
+    /// Returns size of the connected panel
+    pub fn panel_size(&self) -> (u16, u16) {
+        (self.panel_width, self.panel_height)
+    }
+
+    /// Returns rectangle covering the whole panel
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0, 0, u32::from(self.panel_width), u32::from(self.panel_height))
+    }
+
+    /// Returns timing currently used for read operations
+    pub fn read_timing(&self) -> Timing {
+        current_read_timing()
+    }
+
+    /// Returns timing currently used for write operations
+    pub fn write_timing(&self) -> Timing {
+        if self.extended_mode {
+            current_write_timing()
+        } else {
+            current_read_timing()
+        }
+    }
+
+    /// Whether separate read and write timings are configured
+    ///
+    /// Otherwise the same timing is used for both reads and writes.
+    pub fn has_write_timing(&self) -> bool {
+        self.extended_mode
+    }
+
+    /// Changes timing of read operations
+    ///
+    /// Reads from display memory are usually much slower than writes, so
+    /// read timing may be relaxed only for the time of read-back.
+    ///
+    /// If separate read and write timings are not configured (see
+    /// [`FsmcLcd::has_write_timing`]), the same timing is used for reads and
+    /// writes, so this changes write timing too.
+    pub fn set_read_timing(&mut self, timing: &Timing) {
+        configure_read_timing(timing);
+    }
+
+    /// Changes timing of write operations
+    ///
+    /// Many controllers need slow timing during reset and initialization,
+    /// but accept much faster writes afterwards.
+    ///
+    /// If separate read and write timings are not configured (see
+    /// [`FsmcLcd::has_write_timing`]), the same timing is used for reads and
+    /// writes, so this changes read timing too.
+    pub fn set_write_timing(&mut self, timing: &Timing) {
+        if self.extended_mode {
+            configure_write_timing(timing);
+        } else {
+            configure_read_timing(timing);
         }
     }
 
+    /// Runs `f` with given read timing, restoring previous timing afterwards
+    ///
+    /// Without separate write timing writes done by `f` use given timing
+    /// too, see [`FsmcLcd::set_read_timing`].
+    pub fn with_read_timing<R>(&mut self, timing: &Timing, f: impl FnOnce(&mut Self) -> R) -> R {
+        let previous = self.read_timing();
+        self.set_read_timing(timing);
+        let result = f(self);
+        self.set_read_timing(&previous);
+        result
+    }
+
+    /// Runs `f` with given write timing, restoring previous timing afterwards
+    ///
+    /// Without separate write timing reads done by `f` use given timing too,
+    /// see [`FsmcLcd::set_write_timing`].
+    pub fn with_write_timing<R>(&mut self, timing: &Timing, f: impl FnOnce(&mut Self) -> R) -> R {
+        let previous = self.write_timing();
+        self.set_write_timing(timing);
+        let result = f(self);
+        self.set_write_timing(&previous);
+        result
+    }
+
     /// Writes a command value to the display
     ///
     /// This performs a write to the command address, which will set the
     /// register select (RS) line appropriately.
     #[inline]
     pub fn write_command(&self, value: u16) {
-        unsafe {
-            core::ptr::write_volatile(COMMAND_ADDRESS as *mut u16, value);
-        }
+        bus_write(self.command_address, value);
     }
 
     /// Writes a data value to the display
@@ -330,75 +638,113 @@ impl<'d> FsmcLcd<'d> {
     /// register select (RS) line appropriately.
     #[inline]
     pub fn write_data(&self, value: u16) {
-        unsafe {
-            core::ptr::write_volatile(DATA_ADDRESS as *mut u16, value);
+        bus_write(self.data_address, value);
+    }
+
+    /// Writes the same data value `count` times
+    ///
+    /// Used to fill display memory with single color. Loop is unrolled, so
+    /// bus is kept busy.
+    pub fn fill_data(&self, value: u16, count: u32) {
+        for _ in 0..count / 8 {
+            for _ in 0..8 {
+                self.write_data(value);
+            }
+        }
+        for _ in 0..count % 8 {
+            self.write_data(value);
         }
     }
+
+    /// Reads status register of the display
+    ///
+    /// This performs a read from the command address. Controllers like
+    /// RA8875 report busy and other flags this way.
+    #[inline]
+    pub fn read_status(&self) -> u16 {
+        bus_read(self.command_address)
+    }
+
+    /// Returns command address
+    pub(crate) fn command_address(&self) -> *mut u16 {
+        self.command_address as *mut u16
+    }
+
+    /// Returns data address, used as DMA target
+    pub(crate) fn data_address(&self) -> *mut u16 {
+        self.data_address as *mut u16
+    }
+
+    /// Sets addresses used for commands and data
+    pub(crate) fn set_addresses(&mut self, command: usize, data: usize) {
+        self.command_address = command;
+        self.data_address = data;
+    }
+
+    /// Reads a data value from the display
+    ///
+    /// This performs a read from the data address. Note that most controllers
+    /// require a dummy read after a read command is sent.
+    #[inline]
+    pub fn read_data(&self) -> u16 {
+        bus_read(self.data_address)
+    }
 }
 
 // Implement DisplayInterface WriteOnlyDataCommand trait
 impl<'d> WriteOnlyDataCommand for FsmcLcd<'d> {
     fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
-        match cmd {
-            DataFormat::U8(items) => {
-                for item in items {
-                    self.write_command(u16::from(*item));
-                }
-            }
-            DataFormat::U16(items) => {
-                for item in items {
-                    self.write_command(*item);
-                }
-            }
-            DataFormat::U16BE(items) | DataFormat::U16LE(items) => {
-                for item in items {
-                    self.write_command(*item);
-                }
-            }
-            DataFormat::U8Iter(iterator) => {
-                for item in iterator {
-                    self.write_command(u16::from(item));
-                }
-            }
-            DataFormat::U16BEIter(iterator) | DataFormat::U16LEIter(iterator) => {
-                for item in iterator {
-                    self.write_command(item);
-                }
-            }
-            _ => return Err(DisplayError::DataFormatNotImplemented),
-        }
-        Ok(())
+        send_format(cmd, |value| self.write_command(value))
     }
 
     fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
-        match buf {
-            DataFormat::U8(items) => {
-                for item in items {
-                    self.write_data(u16::from(*item));
-                }
+        send_format(buf, |value| self.write_data(value))
+    }
+}
+
+/// Writes value to FSMC address
+#[inline]
+fn bus_write(address: usize, value: u16) {
+    unsafe {
+        core::ptr::write_volatile(address as *mut u16, value);
+    }
+}
+
+/// Reads value from FSMC address
+#[inline]
+fn bus_read(address: usize) -> u16 {
+    unsafe { core::ptr::read_volatile(address as *const u16) }
+}
+
+/// Writes all values of `format` with `write`, bytes are zero-extended
+fn send_format(format: DataFormat<'_>, mut write: impl FnMut(u16)) -> Result<(), DisplayError> {
+    match format {
+        DataFormat::U8(items) => {
+            for item in items {
+                write(u16::from(*item));
             }
-            DataFormat::U16(items) => {
-                for item in items {
-                    self.write_data(*item);
-                }
+        }
+        DataFormat::U16(items) => {
+            for item in items {
+                write(*item);
             }
-            DataFormat::U16BE(items) | DataFormat::U16LE(items) => {
-                for item in items {
-                    self.write_data(*item);
-                }
+        }
+        DataFormat::U16BE(items) | DataFormat::U16LE(items) => {
+            for item in items {
+                write(*item);
             }
-            DataFormat::U8Iter(iterator) => {
-                for item in iterator {
-                    self.write_data(u16::from(item));
-                }
+        }
+        DataFormat::U8Iter(iterator) => {
+            for item in iterator {
+                write(u16::from(item));
             }
-            DataFormat::U16BEIter(iterator) | DataFormat::U16LEIter(iterator) => {
-                for item in iterator {
-                    self.write_data(item);
-                }
+        }
+        DataFormat::U16BEIter(iterator) | DataFormat::U16LEIter(iterator) => {
+            for item in iterator {
+                write(item);
             }
-            _ => return Err(DisplayError::DataFormatNotImplemented),
         }
-        Ok(())
+        _ => return Err(DisplayError::DataFormatNotImplemented),
     }
-}
\ No newline at end of file
+    Ok(())
+}
@@ -0,0 +1,265 @@
+//! LVGL flush callback adapter
+//!
+//! [`LvglFlush`] implements what LVGL display flush callback has to do:
+//! sets display window to flushed area, streams area pixels to display and
+//! reports that flush is ready. It doesn't depend on particular LVGL
+//! bindings, flush callback passes area, pixel buffer and a function which
+//! calls `lv_disp_flush_ready` (`lv_display_flush_ready` in LVGL 9).
+//!
+//! LVGL must be configured for 16-bit colors. When `LV_COLOR_16_SWAP` is
+//! enabled, use [`LvglFlush::swap_bytes`], pixels are swapped back in place.
+//!
+//! With DMA, flush only starts transfer and returns, so LVGL may render into
+//! another buffer meanwhile. Transfer complete interrupt of the DMA stream
+//! is enabled, and [`LvglFlush::on_interrupt`] called from the stream
+//! interrupt handler starts next part of the area or calls ready callback.
+//!
+//! embassy-stm32 with `rt` feature defines DMA interrupt handlers itself, so
+//! the handler can't be replaced there. Then completion is checked by
+//! [`LvglFlush::poll`] instead, which must be called periodically, e.g. from
+//! LVGL `wait_cb` and from the loop calling `lv_timer_handler`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use core::ffi::c_void;
+//! use embassy_stm32_fsmc_display_interface::FsmcLcd;
+//! use embassy_stm32_fsmc_display_interface::lvgl::{FlushArea, LvglFlush};
+//! # fn run(lcd: &FsmcLcd<'_>, drv: *mut c_void, x1: i32, y1: i32, x2: i32, y2: i32,
+//! #        pixels: &mut [u16], flush_ready: fn(*mut c_void)) {
+//!
+//! let mut flush = LvglFlush::new(lcd).swap_bytes(true);
+//!
+//! // In flush callback
+//! unsafe { flush.flush(&FlushArea::new(x1, y1, x2, y2), pixels, flush_ready, drv) };
+//! # }
+//! ```
+
+use core::ffi::c_void;
+
+use display_interface::DisplayError;
+
+use crate::FsmcLcd;
+use crate::dma::FsmcDma;
+
+/// Function reporting that flush is finished, called with context pointer
+/// given to [`LvglFlush::flush`]
+pub type ReadyCallback = fn(*mut c_void);
+
+/// Area being flushed, corners are inclusive as in `lv_area_t`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlushArea {
+    /// Left column
+    pub x1: i32,
+    /// Top row
+    pub y1: i32,
+    /// Right column
+    pub x2: i32,
+    /// Bottom row
+    pub y2: i32,
+}
+
+impl FlushArea {
+    /// Creates area from its corners
+    pub const fn new(x1: i32, y1: i32, x2: i32, y2: i32) -> Self {
+        Self { x1, y1, x2, y2 }
+    }
+
+    /// Number of pixels in area, `None` if area is empty or doesn't fit
+    /// display coordinates
+    fn len(&self) -> Option<usize> {
+        let coords = [self.x1, self.y1, self.x2, self.y2];
+        if coords.iter().any(|coord| u16::try_from(*coord).is_err())
+            || self.x2 < self.x1
+            || self.y2 < self.y1
+        {
+            return None;
+        }
+        Some((self.x2 - self.x1 + 1) as usize * (self.y2 - self.y1 + 1) as usize)
+    }
+}
+
+/// DMA flush in progress
+struct PendingFlush {
+    pixels: *const u16,
+    len: usize,
+    started: usize,
+    ready: ReadyCallback,
+    context: *mut c_void,
+}
+
+// SAFETY: pointers are only used to read pixels and to pass context to
+// ready callback. Caller of `flush` keeps pixels valid until ready is called,
+// from whatever context, e.g. DMA interrupt handler.
+unsafe impl Send for PendingFlush {}
+
+/// Flush callback implementation
+pub struct LvglFlush<'a, 'd> {
+    lcd: &'a FsmcLcd<'d>,
+    dma: Option<FsmcDma<'d>>,
+    swap_bytes: bool,
+    pending: Option<PendingFlush>,
+    error: Option<DisplayError>,
+}
+
+// Adapter must be movable into a static shared with interrupt handler
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<LvglFlush<'static, 'static>>();
+};
+
+impl<'a, 'd> LvglFlush<'a, 'd> {
+    /// Creates adapter writing pixels by CPU
+    pub fn new(lcd: &'a FsmcLcd<'d>) -> Self {
+        Self {
+            lcd,
+            dma: None,
+            swap_bytes: false,
+            pending: None,
+            error: None,
+        }
+    }
+
+    /// Creates adapter writing pixels by DMA, enables transfer complete
+    /// interrupt of the stream
+    ///
+    /// Nothing else must access display while transfer is in progress, see
+    /// [`LvglFlush::is_busy`].
+    pub fn with_dma(lcd: &'a FsmcLcd<'d>, mut dma: FsmcDma<'d>) -> Self {
+        dma.set_interrupt(true);
+        Self {
+            dma: Some(dma),
+            ..Self::new(lcd)
+        }
+    }
+
+    /// Sets whether pixel bytes are swapped (`LV_COLOR_16_SWAP`)
+    pub fn swap_bytes(mut self, swap_bytes: bool) -> Self {
+        self.swap_bytes = swap_bytes;
+        self
+    }
+
+    /// Whether DMA flush is in progress
+    pub fn is_busy(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Returns error of the last failed DMA flush
+    ///
+    /// Failed flush is abandoned and its ready callback is called, so LVGL
+    /// doesn't wait forever.
+    pub fn take_error(&mut self) -> Option<DisplayError> {
+        self.error.take()
+    }
+
+    /// Flushes area
+    ///
+    /// Without DMA pixels are written and `ready` is called before return.
+    /// With DMA transfer is started and `ready` is called later from
+    /// [`LvglFlush::poll`]. If previous flush is still in progress, it is
+    /// finished first. Invalid areas are skipped.
+    ///
+    /// # Safety
+    ///
+    /// With DMA `pixels` must stay valid and unchanged until `ready` is
+    /// called, which LVGL guarantees for its draw buffers. `ready` may be
+    /// called with `context` from [`LvglFlush::on_interrupt`], i.e. from
+    /// interrupt handler.
+    pub unsafe fn flush(
+        &mut self,
+        area: &FlushArea,
+        pixels: &mut [u16],
+        ready: ReadyCallback,
+        context: *mut c_void,
+    ) {
+        while self.poll() {}
+
+        let Some(len) = area.len() else {
+            ready(context);
+            return;
+        };
+        let len = len.min(pixels.len());
+        let pixels = &mut pixels[..len];
+        if self.swap_bytes {
+            for pixel in pixels.iter_mut() {
+                *pixel = pixel.swap_bytes();
+            }
+        }
+
+        self.lcd.write_window(area.x1 as u16, area.y1 as u16, area.x2 as u16, area.y2 as u16);
+        match &mut self.dma {
+            Some(dma) => {
+                // SAFETY: caller keeps pixels valid until ready is called
+                let started = match unsafe { dma.start(self.lcd.data_address(), pixels) } {
+                    Ok(started) => started,
+                    Err(error) => {
+                        self.error = Some(error);
+                        ready(context);
+                        return;
+                    }
+                };
+                self.pending = Some(PendingFlush {
+                    pixels: pixels.as_ptr(),
+                    len: pixels.len(),
+                    started,
+                    ready,
+                    context,
+                });
+            }
+            None => {
+                for pixel in pixels.iter() {
+                    self.lcd.write_data(*pixel);
+                }
+                ready(context);
+            }
+        }
+    }
+
+    /// Handles transfer complete interrupt of the DMA stream
+    ///
+    /// Must be called from the DMA2 stream interrupt handler, e.g. from
+    /// `DMA2_STREAM0` for `DMA2_CH0`. Starts transfer of the next part of
+    /// the area, or calls ready callback if flush is finished. Adapter must
+    /// be shared with the handler, e.g. through
+    /// `critical_section::Mutex<RefCell<LvglFlush>>`.
+    pub fn on_interrupt(&mut self) {
+        self.poll();
+    }
+
+    /// Checks DMA flush progress, calls ready callback when it is finished
+    ///
+    /// Returns whether flush is still in progress.
+    pub fn poll(&mut self) -> bool {
+        let (Some(dma), Some(pending)) = (&mut self.dma, &mut self.pending) else {
+            return false;
+        };
+        if dma.is_busy() {
+            return true;
+        }
+        let mut result = dma.wait_blocking();
+        if result.is_ok() && pending.started < pending.len {
+            // SAFETY: caller of `flush` keeps pixels valid until ready is
+            // called
+            let rest = unsafe {
+                core::slice::from_raw_parts(
+                    pending.pixels.add(pending.started),
+                    pending.len - pending.started,
+                )
+            };
+            match unsafe { dma.start(self.lcd.data_address(), rest) } {
+                Ok(started) => {
+                    pending.started += started;
+                    return true;
+                }
+                Err(error) => result = Err(error),
+            }
+        }
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+        if let Some(pending) = self.pending.take() {
+            (pending.ready)(pending.context);
+        }
+        false
+    }
+}
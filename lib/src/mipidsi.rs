@@ -0,0 +1,54 @@
+//! Native [`mipidsi`](::mipidsi) interface
+//!
+//! Pixels are written as 16-bit words directly, without splitting them into
+//! bytes, and repeated pixels are written with [`FsmcLcd::fill_data`].
+
+use core::convert::Infallible;
+
+use ::mipidsi::interface::{Interface, InterfaceKind};
+
+use crate::FsmcLcd;
+
+impl<'d> Interface for FsmcLcd<'d> {
+    type Word = u16;
+    type Error = Infallible;
+
+    const KIND: InterfaceKind = InterfaceKind::Parallel16Bit;
+
+    fn send_command(&mut self, command: u8, args: &[u8]) -> Result<(), Self::Error> {
+        self.write_command(u16::from(command));
+        for arg in args {
+            self.write_data(u16::from(*arg));
+        }
+        Ok(())
+    }
+
+    fn send_pixels<const N: usize>(
+        &mut self,
+        pixels: impl IntoIterator<Item = [Self::Word; N]>,
+    ) -> Result<(), Self::Error> {
+        for pixel in pixels {
+            for word in pixel {
+                self.write_data(word);
+            }
+        }
+        Ok(())
+    }
+
+    fn send_repeated_pixel<const N: usize>(
+        &mut self,
+        pixel: [Self::Word; N],
+        count: u32,
+    ) -> Result<(), Self::Error> {
+        if let [word] = pixel.as_slice() {
+            self.fill_data(*word, count);
+        } else {
+            for _ in 0..count {
+                for word in pixel {
+                    self.write_data(word);
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use core::marker::PhantomData;
+
+use crate::{FsmcLcd, bus_read, bus_write};
+
+/// Command half of split display interface
+///
+/// Writes commands to command address. Command parameters are written to
+/// data address, so port can write them too.
+///
+/// # Ordering
+///
+/// FSMC performs writes in the order CPU issues them, but nothing orders
+/// writes done through different ports from different tasks or by DMA.
+/// Command ends memory write started by RAMWR, and parameters written while
+/// pixels are streamed become pixels. Commands must only be sent while data
+/// path is idle, e.g. between frames, which users of ports must agree on,
+/// for example with a signal.
+pub struct CommandPort<'a> {
+    command: usize,
+    data: usize,
+    _lcd: PhantomData<&'a ()>,
+}
+
+impl<'a> CommandPort<'a> {
+    /// Writes command
+    #[inline]
+    pub fn write_command(&self, value: u16) {
+        bus_write(self.command, value);
+    }
+
+    /// Writes command parameter
+    #[inline]
+    pub fn write_param(&self, value: u16) {
+        bus_write(self.data, value);
+    }
+
+    /// Writes command followed by 8-bit parameters
+    pub fn send(&self, command: u8, params: &[u8]) {
+        self.write_command(u16::from(command));
+        for param in params {
+            self.write_param(u16::from(*param));
+        }
+    }
+
+    /// Reads status register of the display
+    #[inline]
+    pub fn read_status(&self) -> u16 {
+        bus_read(self.command)
+    }
+
+    /// Returns command address
+    pub fn address(&self) -> *mut u16 {
+        self.command as *mut u16
+    }
+}
+
+/// Data half of split display interface
+///
+/// See [`CommandPort`] for ordering contract.
+pub struct DataPort<'a> {
+    data: usize,
+    _lcd: PhantomData<&'a ()>,
+}
+
+impl<'a> DataPort<'a> {
+    /// Writes data value
+    #[inline]
+    pub fn write(&self, value: u16) {
+        bus_write(self.data, value);
+    }
+
+    /// Writes data values
+    pub fn write_slice(&self, values: &[u16]) {
+        for value in values {
+            self.write(*value);
+        }
+    }
+
+    /// Reads data value
+    #[inline]
+    pub fn read(&self) -> u16 {
+        bus_read(self.data)
+    }
+
+    /// Returns data address
+    ///
+    /// Every write to it goes to display as data, writes must be 16-bit and
+    /// always target this exact address, e.g. DMA must not increment
+    /// destination address. [`FsmcDma::write_to`] streams pixels to it.
+    ///
+    /// [`FsmcDma::write_to`]: crate::dma::FsmcDma::write_to
+    pub fn address(&self) -> *mut u16 {
+        self.data as *mut u16
+    }
+}
+
+impl<'d> FsmcLcd<'d> {
+    /// Splits interface into command and data ports
+    ///
+    /// Ports may be used from different tasks, see [`CommandPort`] for
+    /// ordering contract. Interface is borrowed while ports exist.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use embassy_stm32_fsmc_display_interface::FsmcLcd;
+    /// # fn run(lcd: &'static mut FsmcLcd<'static>) {
+    ///
+    /// let (command, data) = lcd.split();
+    /// // Control task
+    /// command.send(0x2A, &[0, 0, 0, 239]);
+    /// command.send(0x2B, &[0, 0, 1, 63]);
+    /// command.write_command(0x2C);
+    /// // Streaming task, after control task is done
+    /// # let dma: &mut embassy_stm32_fsmc_display_interface::dma::FsmcDma<'_> = todo!();
+    /// # async {
+    /// dma.write_to(&data, &[0xFFFF; 320]).await.unwrap();
+    /// # };
+    /// # }
+    /// ```
+    pub fn split(&mut self) -> (CommandPort<'_>, DataPort<'_>) {
+        ports(self.command_address() as usize, self.data_address() as usize)
+    }
+}
+
+/// Creates ports writing to given addresses
+pub(crate) fn ports<'a>(command: usize, data: usize) -> (CommandPort<'a>, DataPort<'a>) {
+    (
+        CommandPort {
+            command,
+            data,
+            _lcd: PhantomData,
+        },
+        DataPort {
+            data,
+            _lcd: PhantomData,
+        },
+    )
+}
@@ -0,0 +1,101 @@
+//! Display controller detection
+//!
+//! Panels from different vendors often share the same connector, so
+//! firmware may need to find out which controller is fitted. This module
+//! reads identification registers of known controllers using FSMC read
+//! cycle and recognizes controller by the responses.
+//!
+//! Probing must be done after hardware reset. Read cycle is much slower
+//! than write one, so use conservative read timing (such as
+//! [`Timing::default`](crate::Timing::default)) while probing.
+
+use crate::dcs::Instruction;
+use crate::FsmcLcd;
+
+/// "Read ID4" command, supported by ILI93xx/ILI94xx controllers
+const READ_ID4: u8 = 0xD3;
+/// "Read ID1" command
+const READ_ID1: u8 = 0xDA;
+/// "Read ID2" command
+const READ_ID2: u8 = 0xDB;
+/// "Read ID3" command
+const READ_ID3: u8 = 0xDC;
+/// "Read device code" command, supported by HX8357-B/ILI9481 controllers
+const READ_DEVICE_CODE: u8 = 0xBF;
+/// "Read ID" command of HX8357-D, which responds with 0x99
+const READ_HX8357D_ID: u8 = 0xD0;
+
+/// Raw responses to identification commands
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ControllerIds {
+    /// Response to "read display ID" (0x04)
+    pub display_id: [u8; 3],
+    /// Response to "read ID4" (0xD3)
+    pub id4: [u8; 3],
+    /// Responses to "read ID1", "read ID2" and "read ID3" (0xDA-0xDC)
+    pub id1_3: [u8; 3],
+    /// Response to "read device code" (0xBF)
+    pub device_code: [u8; 5],
+    /// Response to HX8357-D "read ID" (0xD0)
+    pub hx8357d_id: [u8; 3],
+}
+
+/// Detected display controller
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DetectedController {
+    /// ILI9341, ID4 is 93 41
+    Ili9341,
+    /// ILI9486, ID4 is 94 86
+    Ili9486,
+    /// ST7789V, display ID is 85 52
+    St7789v,
+    /// HX8357-B, device code is 83 57
+    Hx8357b,
+    /// HX8357-D, "read ID" (0xD0) returns 0x99
+    Hx8357d,
+    /// Controller is not recognized, raw responses are provided
+    Unknown(ControllerIds),
+}
+
+impl DetectedController {
+    /// Recognizes controller by responses to identification commands
+    pub fn from_ids(ids: &ControllerIds) -> Self {
+        match ids.id4 {
+            [_, 0x93, 0x41] => return Self::Ili9341,
+            [_, 0x94, 0x86] => return Self::Ili9486,
+            _ => {}
+        }
+        if ids.display_id[1..] == [0x85, 0x52] || ids.id1_3[1..] == [0x85, 0x52] {
+            return Self::St7789v;
+        }
+        if ids.device_code.windows(2).any(|w| w == [0x83, 0x57]) {
+            return Self::Hx8357b;
+        }
+        if ids.hx8357d_id[0] == 0x99 {
+            return Self::Hx8357d;
+        }
+        Self::Unknown(*ids)
+    }
+}
+
+impl<'d> FsmcLcd<'d> {
+    /// Reads responses to all known identification commands
+    pub fn read_controller_ids(&self) -> ControllerIds {
+        let mut ids = ControllerIds::default();
+        self.read_register(Instruction::ReadDisplayId as u8, &mut ids.display_id);
+        self.read_register(READ_ID4, &mut ids.id4);
+        for (byte, cmd) in ids.id1_3.iter_mut().zip([READ_ID1, READ_ID2, READ_ID3]) {
+            self.read_register(cmd, core::slice::from_mut(byte));
+        }
+        self.read_register(READ_DEVICE_CODE, &mut ids.device_code);
+        self.read_register(READ_HX8357D_ID, &mut ids.hx8357d_id);
+        ids
+    }
+
+    /// Detects connected display controller
+    ///
+    /// See [module documentation](crate::probe) for requirements.
+    pub fn probe(&self) -> DetectedController {
+        DetectedController::from_ids(&self.read_controller_ids())
+    }
+}
@@ -0,0 +1,113 @@
+use display_interface::DisplayError;
+
+use crate::dcs::{Command, Instruction};
+use crate::{FsmcLcd, Rect};
+
+/// Converts 8-bit color components into RGB565 pixel
+///
+/// Controllers return 6-bit components aligned to the top of a byte.
+pub(crate) fn rgb565_from_components(r: u8, g: u8, b: u8) -> u16 {
+    (u16::from(r >> 3) << 11) | (u16::from(g >> 2) << 5) | u16::from(b >> 3)
+}
+
+impl<'d> FsmcLcd<'d> {
+    /// Sends memory read command and returns stream of pixels, skipping
+    /// dummy read
+    ///
+    /// In 16-bit mode controllers return pixels in RGB666 format, packed as
+    /// a stream of one byte per component, two components per bus word, so
+    /// pixels don't start on word boundary. Stream must be consumed as a
+    /// whole, its pixels can't be split between several commands.
+    fn pixel_stream(&self, instruction: Instruction) -> impl Iterator<Item = u16> + '_ {
+        self.write_command(instruction as u16);
+        // Dummy read
+        self.read_data();
+
+        let mut components =
+            core::iter::from_fn(|| Some(self.read_data())).flat_map(u16::to_be_bytes);
+        core::iter::from_fn(move || {
+            let mut next = || components.next().unwrap_or_default();
+            let (r, g, b) = (next(), next(), next());
+            Some(rgb565_from_components(r, g, b))
+        })
+    }
+
+    /// Checks that `rect` is inside of the panel and sets it as memory
+    /// window
+    pub(crate) fn set_read_window(&self, rect: &Rect) -> Result<(), DisplayError> {
+        if rect.is_empty() || rect.intersection(&self.bounds()) != Some(*rect) {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        self.write_dcs(Command::SetColumnAddress {
+            start: rect.x as u16,
+            end: (rect.x + rect.width as i32 - 1) as u16,
+        });
+        self.write_dcs(Command::SetPageAddress {
+            start: rect.y as u16,
+            end: (rect.y + rect.height as i32 - 1) as u16,
+        });
+        Ok(())
+    }
+
+    /// Reads pixels from display memory
+    ///
+    /// Reads `buf.len()` pixels of `rect` row by row, starting from top-left
+    /// corner, and converts them to RGB565. Use
+    /// [`FsmcLcd::read_pixels_continue`] to read following pixels.
+    ///
+    /// Returns [`DisplayError::OutOfBoundsError`] if `rect` is not inside of
+    /// the panel or `buf` is larger than `rect`.
+    pub fn read_pixels(&self, rect: Rect, buf: &mut [u16]) -> Result<(), DisplayError> {
+        if buf.len() as u64 > rect.area() {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        self.set_read_window(&rect)?;
+        for (pixel, value) in buf.iter_mut().zip(self.pixel_stream(Instruction::ReadMemoryStart)) {
+            *pixel = value;
+        }
+        Ok(())
+    }
+
+    /// Continues reading pixels from display memory
+    ///
+    /// Reads pixels following the ones read by previous call to
+    /// [`FsmcLcd::read_pixels`] or this method.
+    ///
+    /// Two pixels take three bus words, so previous call must have read even
+    /// number of pixels. Otherwise half of the next pixel was already read
+    /// and discarded, and controller continues from the pixel after it.
+    pub fn read_pixels_continue(&self, buf: &mut [u16]) {
+        let stream = self.pixel_stream(Instruction::ReadMemoryContinue);
+        for (pixel, value) in buf.iter_mut().zip(stream) {
+            *pixel = value;
+        }
+    }
+
+    /// Reads region of display memory and logs it using defmt
+    ///
+    /// Region is logged as a `fsmc-screenshot-begin` line with region size,
+    /// followed by `fsmc-screenshot-row` line with row index and RGB565 pixels
+    /// for each row, followed by `fsmc-screenshot-end` line. Host tool may
+    /// collect these lines from RTT log to rebuild the image.
+    ///
+    /// `line_buf` must be able to hold single row of the region.
+    #[cfg(feature = "defmt")]
+    pub fn dump_region(&self, rect: Rect, line_buf: &mut [u16]) -> Result<(), DisplayError> {
+        let line = line_buf
+            .get_mut(..rect.width as usize)
+            .ok_or(DisplayError::OutOfBoundsError)?;
+        self.set_read_window(&rect)?;
+
+        defmt::info!("fsmc-screenshot-begin {=u32} {=u32}", rect.width, rect.height);
+        // Single read command for the whole region, rows may be odd
+        let mut stream = self.pixel_stream(Instruction::ReadMemoryStart);
+        for row in 0..rect.height {
+            for pixel in line.iter_mut() {
+                *pixel = stream.next().unwrap_or_default();
+            }
+            defmt::info!("fsmc-screenshot-row {=u32} {=[?]}", row, line);
+        }
+        defmt::info!("fsmc-screenshot-end");
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+/// Rectangular area on the display
+///
+/// Origin may lie outside of the display, in which case the rectangle is
+/// clipped by operations that use it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rect {
+    /// Left column
+    pub x: i32,
+    /// Top row
+    pub y: i32,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+}
+
+impl Rect {
+    /// Creates a new rectangle
+    pub const fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Whether rectangle contains no pixels
+    pub const fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns common part of two rectangles, `None` if they don't overlap
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = (self.x as i64 + self.width as i64).min(other.x as i64 + other.width as i64);
+        let y1 = (self.y as i64 + self.height as i64).min(other.y as i64 + other.height as i64);
+        if x1 <= x0 as i64 || y1 <= y0 as i64 {
+            return None;
+        }
+        Some(Rect::new(x0, y0, (x1 - x0 as i64) as u32, (y1 - y0 as i64) as u32))
+    }
+
+    /// Returns the smallest rectangle containing both rectangles
+    ///
+    /// Empty rectangles are ignored.
+    pub fn union(&self, other: &Rect) -> Rect {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+        let x0 = self.x.min(other.x);
+        let y0 = self.y.min(other.y);
+        let x1 = (self.x as i64 + self.width as i64).max(other.x as i64 + other.width as i64);
+        let y1 = (self.y as i64 + self.height as i64).max(other.y as i64 + other.height as i64);
+        Rect::new(x0, y0, (x1 - x0 as i64) as u32, (y1 - y0 as i64) as u32)
+    }
+
+    /// Number of pixels in rectangle
+    pub const fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+}
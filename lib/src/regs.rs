@@ -0,0 +1,71 @@
+use crate::FsmcLcd;
+
+impl<'d> FsmcLcd<'d> {
+    /// Writes 16-bit register of register-indexed controller
+    ///
+    /// Index is written to the command address, followed by value written
+    /// to the data address. This is how ILI9325-like controllers and
+    /// NT35510 (which uses 16-bit command addresses) are programmed.
+    #[inline]
+    pub fn write_reg(&self, index: u16, value: u16) {
+        self.write_command(index);
+        self.write_data(value);
+    }
+
+    /// Reads 16-bit register of register-indexed controller
+    ///
+    /// Unlike DCS commands, registers of ILI93xx controllers are read
+    /// without dummy read.
+    #[inline]
+    pub fn read_reg(&self, index: u16) -> u16 {
+        self.write_command(index);
+        self.read_data()
+    }
+
+    /// Writes sequence of `(index, value)` register pairs
+    ///
+    /// ```no_run
+    /// # use embassy_stm32_fsmc_display_interface::FsmcLcd;
+    /// # let lcd: FsmcLcd<'static> = todo!();
+    /// // ILI9325: set GRAM address and window
+    /// lcd.write_regs(&[
+    ///     (0x0020, 0), (0x0021, 0),
+    ///     (0x0050, 0), (0x0051, 239),
+    ///     (0x0052, 0), (0x0053, 319),
+    /// ]);
+    /// ```
+    pub fn write_regs(&self, regs: &[(u16, u16)]) {
+        for &(index, value) in regs {
+            self.write_reg(index, value);
+        }
+    }
+
+    /// Writes values to consecutive registers starting from `base`
+    ///
+    /// Register index wraps around after 0xFFFF.
+    ///
+    /// NT35510 uses separate 16-bit command address for each parameter, e.g.
+    /// column address is set with 0x2A00-0x2A03:
+    ///
+    /// ```no_run
+    /// # use embassy_stm32_fsmc_display_interface::FsmcLcd;
+    /// # let lcd: FsmcLcd<'static> = todo!();
+    /// // Columns 0-479
+    /// lcd.write_reg_block(0x2A00, &[0x00, 0x00, 0x01, 0xDF]);
+    /// ```
+    pub fn write_reg_block(&self, base: u16, values: &[u16]) {
+        for (offset, &value) in values.iter().enumerate() {
+            self.write_reg(base.wrapping_add(offset as u16), value);
+        }
+    }
+
+    /// Reads consecutive registers starting from `base` into `buf`
+    ///
+    /// Register index wraps around after 0xFFFF, as in
+    /// [`FsmcLcd::write_reg_block`].
+    pub fn read_reg_block(&self, base: u16, buf: &mut [u16]) {
+        for (offset, value) in buf.iter_mut().enumerate() {
+            *value = self.read_reg(base.wrapping_add(offset as u16));
+        }
+    }
+}
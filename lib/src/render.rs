@@ -0,0 +1,177 @@
+//! Rendering through small line or tile buffers
+//!
+//! Full frame buffer doesn't fit into internal RAM for bigger panels.
+//! [`LineRenderer`] asks a callback to render image part by part into one
+//! of two small buffers. While CPU fills one buffer, the other one is
+//! transferred to display by DMA, so rendering and transfer overlap.
+//!
+//! By default parts are bands of full lines, all written into single
+//! display window. [`LineRenderer::render_tiles`] splits area into tiles
+//! instead, each tile is written into its own window.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embassy_stm32_fsmc_display_interface::{FsmcLcd, Rect};
+//! use embassy_stm32_fsmc_display_interface::dma::FsmcDma;
+//! use embassy_stm32_fsmc_display_interface::render::LineRenderer;
+//! # async fn run(lcd: &mut FsmcLcd<'_>, dma: FsmcDma<'_>) {
+//! # let (a, b): (&mut [u16; 3200], &mut [u16; 3200]) = todo!();
+//!
+//! // Two 10-line buffers for 320x480 panel
+//! let mut renderer = LineRenderer::new(dma, a, b);
+//! let screen = lcd.bounds();
+//! renderer
+//!     .render(lcd, screen, |band, pixels| {
+//!         // Vertical gradient
+//!         for (row, line) in pixels.chunks_mut(band.width as usize).enumerate() {
+//!             line.fill((band.y as u16 + row as u16) >> 4);
+//!         }
+//!     })
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+
+use display_interface::DisplayError;
+
+use crate::dma::{AbortOnDrop, FsmcDma};
+use crate::{FsmcLcd, Rect};
+
+/// Transfer of a buffer part which is in progress
+struct Pending {
+    /// Buffer index
+    buffer: usize,
+    /// Number of pixels whose transfer is already started
+    started: usize,
+    /// Number of pixels to transfer
+    len: usize,
+}
+
+/// Renderer filling two buffers in turn
+pub struct LineRenderer<'a, 'd> {
+    dma: FsmcDma<'d>,
+    buffers: [&'a mut [u16]; 2],
+}
+
+impl<'a, 'd> LineRenderer<'a, 'd> {
+    /// Creates renderer using two buffers
+    ///
+    /// Buffers should be word-aligned for faster DMA transfers, only the
+    /// size of the smaller one is used.
+    pub fn new(dma: FsmcDma<'d>, first: &'a mut [u16], second: &'a mut [u16]) -> Self {
+        Self {
+            dma,
+            buffers: [first, second],
+        }
+    }
+
+    /// Returns DMA stream
+    pub fn release(self) -> FsmcDma<'d> {
+        self.dma
+    }
+
+    /// Renders area band by band
+    ///
+    /// Area is clipped to panel. `fill` is called with band rectangle and
+    /// buffer which must be filled with its pixels row by row. Each band has
+    /// the width of the area and as many lines as fit into buffer.
+    ///
+    /// Returns [`DisplayError::OutOfBoundsError`] if buffers can't hold
+    /// single line, [`DisplayError::BusWriteError`] if DMA transfer failed.
+    pub async fn render(
+        &mut self,
+        lcd: &mut FsmcLcd<'_>,
+        rect: Rect,
+        fill: impl FnMut(Rect, &mut [u16]),
+    ) -> Result<(), DisplayError> {
+        self.render_tiles(lcd, rect, rect.width, fill).await
+    }
+
+    /// Renders area tile by tile
+    ///
+    /// Same as [`LineRenderer::render`], but area is split into tiles of
+    /// `tile_width` columns and as many rows as fit into buffer. Tiles are
+    /// rendered row by row, from left to right.
+    pub async fn render_tiles(
+        &mut self,
+        lcd: &mut FsmcLcd<'_>,
+        rect: Rect,
+        tile_width: u32,
+        mut fill: impl FnMut(Rect, &mut [u16]),
+    ) -> Result<(), DisplayError> {
+        let Some(rect) = rect.intersection(&lcd.bounds()) else {
+            return Ok(());
+        };
+        let tile_width = tile_width.min(rect.width);
+        let capacity = self.buffers[0].len().min(self.buffers[1].len());
+        let tile_height = (capacity as u64 / u64::from(tile_width.max(1)))
+            .min(u64::from(rect.height)) as u32;
+        if tile_width == 0 || tile_height == 0 {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+
+        let single_window = tile_width == rect.width;
+        if single_window {
+            write_rect_window(lcd, &rect);
+        }
+
+        let guard = AbortOnDrop(&mut self.dma);
+        let mut pending: Option<Pending> = None;
+        let mut current = 0;
+        for y in (0..rect.height).step_by(tile_height as usize) {
+            for x in (0..rect.width).step_by(tile_width as usize) {
+                let tile = Rect::new(
+                    rect.x + x as i32,
+                    rect.y + y as i32,
+                    tile_width.min(rect.width - x),
+                    tile_height.min(rect.height - y),
+                );
+                let len = tile.area() as usize;
+                fill(tile, &mut self.buffers[current][..len]);
+
+                finish(guard.0, lcd, &self.buffers, pending.take()).await?;
+                if !single_window {
+                    write_rect_window(lcd, &tile);
+                }
+                let target = lcd.data_address();
+                // SAFETY: buffer is not touched until transfer is finished,
+                // guard aborts transfer if future is cancelled
+                let started = unsafe { guard.0.start(target, &self.buffers[current][..len])? };
+                pending = Some(Pending { buffer: current, started, len });
+                current = 1 - current;
+            }
+        }
+        finish(guard.0, lcd, &self.buffers, pending).await
+    }
+}
+
+/// Sets display window covering rectangle
+fn write_rect_window(lcd: &FsmcLcd<'_>, rect: &Rect) {
+    lcd.write_window(
+        rect.x as u16,
+        rect.y as u16,
+        (rect.x + rect.width as i32 - 1) as u16,
+        (rect.y + rect.height as i32 - 1) as u16,
+    );
+}
+
+/// Waits until pending transfer is finished, starting its remaining parts
+async fn finish(
+    dma: &mut FsmcDma<'_>,
+    lcd: &FsmcLcd<'_>,
+    buffers: &[&mut [u16]; 2],
+    pending: Option<Pending>,
+) -> Result<(), DisplayError> {
+    let Some(Pending { buffer, mut started, len }) = pending else {
+        return Ok(());
+    };
+    loop {
+        dma.wait().await?;
+        if started == len {
+            return Ok(());
+        }
+        // SAFETY: transfer is awaited before returning
+        started += unsafe { dma.start(lcd.data_address(), &buffers[buffer][started..len])? };
+    }
+}
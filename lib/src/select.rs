@@ -0,0 +1,230 @@
+//! Several displays on one FSMC bank
+//!
+//! Besides RS, spare FSMC address lines may drive chip select inputs of
+//! several displays. [`DisplaySelect`] configures such lines and gives one
+//! [`SelectedDisplay`] handle per line, which writes to addresses where its
+//! line is low and all other select lines are high, so exactly one display
+//! with active-low CS is selected. Select lines are connected directly to CS
+//! inputs of displays, FSMC chip select (NE1) isn't used by displays then.
+//!
+//! Every bus access carries full address, so handles of different displays
+//! may be used from different tasks without interleaving issues. Use
+//! [`DisplaySelect::broadcast`] to write to all displays at once.
+//!
+//! [`DisplaySelect::select`] binds [`FsmcLcd`] itself to one display, so
+//! drawing and DMA helpers built on it, e.g. [`FsmcDma`] users, write to
+//! that display. [`FsmcDma::write_to`] writes to data address of a
+//! [`DataPort`] given by [`SelectedDisplay::split`].
+//!
+//! [`FsmcDma`]: crate::dma::FsmcDma
+//! [`FsmcDma::write_to`]: crate::dma::FsmcDma::write_to
+//!
+//! # Example
+//!
+//! ```no_run
+//! use display_interface::WriteOnlyDataCommand;
+//! use embassy_stm32_fsmc_display_interface::FsmcLcd;
+//! use embassy_stm32_fsmc_display_interface::select::{DisplaySelect, SelectLine};
+//! # use embassy_stm32::Peri;
+//! # use embassy_stm32::peripherals::{PD11, PD12};
+//! # fn run(lcd: FsmcLcd<'static>, pd11: Peri<'static, PD11>, pd12: Peri<'static, PD12>) {
+//!
+//! // A16 selects first display, A17 second one, A18 is RS
+//! let lines = [SelectLine::new(pd11, 16).unwrap(), SelectLine::new(pd12, 17).unwrap()];
+//! let mut select = DisplaySelect::new(lcd, 18, lines).unwrap();
+//! let [mut first, mut second] = select.displays();
+//! first.send_commands(display_interface::DataFormat::U8(&[0x29])).unwrap();
+//! # }
+//! ```
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embassy_stm32::Peri;
+use embassy_stm32::gpio::{AfType, Flex, OutputType, Pin, Pull, Speed};
+
+use crate::ports::ports;
+use crate::{
+    BASE_ADDRESS, COMMAND_ADDRESS, CommandPort, ConfigError, DATA_ADDRESS, DataPort, FsmcLcd,
+    send_format,
+};
+
+/// Highest FSMC address line available with 16-bit bus
+const MAX_LINE: u8 = 24;
+
+/// Returns address bit driving given address line
+///
+/// Bus is 16-bit, so address line 0 is driven by address bit 1.
+const fn line_bit(line: u8) -> usize {
+    1 << (line as usize + 1)
+}
+
+/// FSMC address line used as display select
+pub struct SelectLine<'d> {
+    line: u8,
+    _pin: Flex<'d>,
+}
+
+impl<'d> SelectLine<'d> {
+    /// Configures pin as FSMC address line `line` (A0-A24)
+    ///
+    /// Pin must be the one carrying given line, e.g. PD11 for A16.
+    pub fn new(pin: Peri<'d, impl Pin>, line: u8) -> Result<Self, ConfigError> {
+        if line > MAX_LINE {
+            return Err(ConfigError::InvalidAddressLine);
+        }
+        let mut pin = Flex::new(pin);
+        pin.set_as_af_unchecked(
+            12,
+            AfType::output_pull(OutputType::PushPull, Speed::VeryHigh, Pull::None),
+        );
+        Ok(Self { line, _pin: pin })
+    }
+
+    /// Returns address line number
+    pub fn line(&self) -> u8 {
+        self.line
+    }
+}
+
+/// Displays selected by extra address lines
+pub struct DisplaySelect<'d, const N: usize> {
+    lcd: FsmcLcd<'d>,
+    lines: [SelectLine<'d>; N],
+    rs_bit: usize,
+}
+
+impl<'d, const N: usize> DisplaySelect<'d, N> {
+    /// Creates display selection
+    ///
+    /// `rs_line` is address line connected to RS of all displays. Returns
+    /// error if any line is out of range or used twice.
+    pub fn new(
+        lcd: FsmcLcd<'d>,
+        rs_line: u8,
+        lines: [SelectLine<'d>; N],
+    ) -> Result<Self, ConfigError> {
+        if rs_line > MAX_LINE {
+            return Err(ConfigError::InvalidAddressLine);
+        }
+        let mut used = line_bit(rs_line);
+        for line in &lines {
+            let bit = line_bit(line.line);
+            if used & bit != 0 {
+                return Err(ConfigError::InvalidAddressLine);
+            }
+            used |= bit;
+        }
+        Ok(Self { lcd, lines, rs_bit: line_bit(rs_line) })
+    }
+
+    /// Returns handle of display selected by `lines[index]`
+    pub fn display(&mut self, index: usize) -> Option<SelectedDisplay<'_>> {
+        let (command, data) = self.addresses(index)?;
+        Some(self.handle(command, data))
+    }
+
+    /// Returns handles of all displays
+    pub fn displays(&mut self) -> [SelectedDisplay<'_>; N] {
+        core::array::from_fn(|index| {
+            let (command, data) = self.addresses(index).unwrap_or_default();
+            self.handle(command, data)
+        })
+    }
+
+    /// Returns interface writing to display selected by `lines[index]`
+    ///
+    /// Everything built on [`FsmcLcd`] works with selected display then,
+    /// including DMA transfers, e.g. of
+    /// [`DoubleBuffer`](crate::double_buffer::DoubleBuffer). Interface stays
+    /// bound to this display until another one is selected.
+    pub fn select(&mut self, index: usize) -> Option<&mut FsmcLcd<'d>> {
+        let (command, data) = self.addresses(index)?;
+        self.lcd.set_addresses(command, data);
+        Some(&mut self.lcd)
+    }
+
+    /// Returns handle selecting all displays at once
+    ///
+    /// Useful for initialization of identical displays. Reads through it are
+    /// meaningless, since all displays drive data bus.
+    pub fn broadcast(&mut self) -> SelectedDisplay<'_> {
+        self.handle(BASE_ADDRESS, BASE_ADDRESS | self.rs_bit)
+    }
+
+    /// Returns display interface, select lines are released
+    pub fn release(mut self) -> FsmcLcd<'d> {
+        self.lcd.set_addresses(COMMAND_ADDRESS, DATA_ADDRESS);
+        self.lcd
+    }
+
+    /// Address bits of all select lines
+    fn all_lines(&self) -> usize {
+        self.lines.iter().fold(0, |bits, line| bits | line_bit(line.line))
+    }
+
+    /// Returns command and data addresses of display selected by
+    /// `lines[index]`
+    fn addresses(&self, index: usize) -> Option<(usize, usize)> {
+        let line = self.lines.get(index)?;
+        let command = BASE_ADDRESS | (self.all_lines() & !line_bit(line.line));
+        Some((command, command | self.rs_bit))
+    }
+
+    /// Creates handle writing to given addresses
+    fn handle(&self, command: usize, data: usize) -> SelectedDisplay<'_> {
+        let (command, data) = ports(command, data);
+        SelectedDisplay { command, data }
+    }
+}
+
+/// Interface of single display of [`DisplaySelect`]
+pub struct SelectedDisplay<'a> {
+    command: CommandPort<'a>,
+    data: DataPort<'a>,
+}
+
+impl<'a> SelectedDisplay<'a> {
+    /// Writes command
+    #[inline]
+    pub fn write_command(&self, value: u16) {
+        self.command.write_command(value);
+    }
+
+    /// Writes data value
+    #[inline]
+    pub fn write_data(&self, value: u16) {
+        self.data.write(value);
+    }
+
+    /// Reads data value
+    #[inline]
+    pub fn read_data(&self) -> u16 {
+        self.data.read()
+    }
+
+    /// Returns command address
+    pub fn command_address(&self) -> *mut u16 {
+        self.command.address()
+    }
+
+    /// Returns data address, e.g. for DMA
+    pub fn data_address(&self) -> *mut u16 {
+        self.data.address()
+    }
+
+    /// Splits handle into command and data ports
+    ///
+    /// See [`CommandPort`] for ordering contract.
+    pub fn split(&mut self) -> (CommandPort<'_>, DataPort<'_>) {
+        ports(self.command_address() as usize, self.data_address() as usize)
+    }
+}
+
+impl WriteOnlyDataCommand for SelectedDisplay<'_> {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        send_format(cmd, |value| self.write_command(value))
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        send_format(buf, |value| self.write_data(value))
+    }
+}
@@ -0,0 +1,244 @@
+//! Display server task
+//!
+//! [`DisplayServer`] is a queue of drawing requests. Single task owns the
+//! display and runs [`DisplayServer::run`], other tasks send requests and
+//! never touch the bus. Requests with higher priority are drawn first,
+//! requests with equal priority are drawn in order they were sent. Sender
+//! may pass a signal which is raised with the result once request is
+//! handled, malformed requests are not drawn and report an error.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embassy_stm32_fsmc_display_interface::Rect;
+//! use embassy_stm32_fsmc_display_interface::display::DcsDisplay;
+//! use embassy_stm32_fsmc_display_interface::server::{DisplayServer, DoneSignal, Request};
+//! use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+//! use embassy_sync::signal::Signal;
+//! use embedded_graphics_core::pixelcolor::{Rgb565, RgbColor};
+//!
+//! static SERVER: DisplayServer<CriticalSectionRawMutex, 16> = DisplayServer::new();
+//! static ALERT_DONE: DoneSignal<CriticalSectionRawMutex> = Signal::new();
+//!
+//! async fn display_task(mut display: DcsDisplay<'static>) -> ! {
+//!     SERVER.run(&mut display, |_| {}).await
+//! }
+//!
+//! async fn alert_task() {
+//!     let request = Request::FillRect { rect: Rect::new(0, 0, 320, 20), color: Rgb565::RED };
+//!     SERVER.send(request, 10, Some(&ALERT_DONE)).await;
+//!     ALERT_DONE.wait().await.unwrap();
+//! }
+//! ```
+
+use core::cmp::Ordering;
+use core::sync::atomic::{AtomicU32, Ordering as AtomicOrdering};
+
+use display_interface::DisplayError;
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::priority_channel::{Max, PriorityChannel};
+use embassy_sync::signal::Signal;
+use embedded_graphics::Drawable;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::text::Text;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::Size;
+use embedded_graphics_core::pixelcolor::Rgb565;
+use embedded_graphics_core::pixelcolor::raw::RawU16;
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::Rect;
+use crate::blit::check_source;
+use crate::display::DcsDisplay;
+
+/// Signal raised with result of handled request
+pub type DoneSignal<M> = Signal<M, Result<(), DisplayError>>;
+
+/// Drawing request
+#[derive(Clone, Copy)]
+pub enum Request {
+    /// Fills rectangle with single color
+    FillRect {
+        /// Filled rectangle
+        rect: Rect,
+        /// Fill color
+        color: Rgb565,
+    },
+    /// Copies image region to display, same as [`FsmcLcd::blit`]
+    ///
+    /// Fails with [`DisplayError::OutOfBoundsError`] if `pixels` is too
+    /// short to hold the region or `stride` is less than region width.
+    ///
+    /// [`FsmcLcd::blit`]: crate::FsmcLcd::blit
+    Blit {
+        /// Destination rectangle
+        rect: Rect,
+        /// Image rows
+        pixels: &'static [u16],
+        /// Number of pixels in image row
+        stride: usize,
+    },
+    /// Draws text, position is the left end of baseline
+    Text {
+        /// Text to draw
+        text: &'static str,
+        /// Text position
+        position: Point,
+        /// Font and colors
+        style: MonoTextStyle<'static, Rgb565>,
+    },
+    /// Marks end of frame, server calls its present hook
+    Present,
+}
+
+/// Request waiting in queue
+struct Queued<M: RawMutex + 'static> {
+    priority: u8,
+    sequence: u32,
+    request: Request,
+    done: Option<&'static DoneSignal<M>>,
+}
+
+impl<M: RawMutex> Queued<M> {
+    /// Ordering key: higher priority first, then earlier request first
+    fn key(&self) -> (u8, core::cmp::Reverse<u32>) {
+        (self.priority, core::cmp::Reverse(self.sequence))
+    }
+}
+
+impl<M: RawMutex> PartialEq for Queued<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<M: RawMutex> Eq for Queued<M> {}
+
+impl<M: RawMutex> PartialOrd for Queued<M> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<M: RawMutex> Ord for Queued<M> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Queue of up to `N` drawing requests
+pub struct DisplayServer<M: RawMutex + 'static, const N: usize> {
+    queue: PriorityChannel<M, Queued<M>, Max, N>,
+    sequence: AtomicU32,
+}
+
+impl<M: RawMutex + 'static, const N: usize> DisplayServer<M, N> {
+    /// Creates empty queue
+    pub const fn new() -> Self {
+        Self {
+            queue: PriorityChannel::new(),
+            sequence: AtomicU32::new(0),
+        }
+    }
+
+    /// Wraps request for queue
+    fn queued(
+        &self,
+        request: Request,
+        priority: u8,
+        done: Option<&'static DoneSignal<M>>,
+    ) -> Queued<M> {
+        let sequence = self.sequence.fetch_add(1, AtomicOrdering::Relaxed);
+        Queued { priority, sequence, request, done }
+    }
+
+    /// Queues request, waiting while queue is full
+    ///
+    /// Requests with higher `priority` are drawn first. `done` is signaled
+    /// with the result once request is handled.
+    pub async fn send(
+        &self,
+        request: Request,
+        priority: u8,
+        done: Option<&'static DoneSignal<M>>,
+    ) {
+        self.queue.send(self.queued(request, priority, done)).await;
+    }
+
+    /// Queues request if there is free space, returns it back otherwise
+    pub fn try_send(
+        &self,
+        request: Request,
+        priority: u8,
+        done: Option<&'static DoneSignal<M>>,
+    ) -> Result<(), Request> {
+        self.queue
+            .try_send(self.queued(request, priority, done))
+            .map_err(|embassy_sync::channel::TrySendError::Full(queued)| queued.request)
+    }
+
+    /// Draws requests forever
+    ///
+    /// `present` is called for [`Request::Present`].
+    pub async fn run(
+        &self,
+        display: &mut DcsDisplay<'_>,
+        mut present: impl FnMut(&mut DcsDisplay<'_>),
+    ) -> ! {
+        loop {
+            let queued = self.queue.receive().await;
+            let result = match queued.request {
+                Request::FillRect { rect, color } => {
+                    let Ok(()) = display.fill_solid(&to_area(&rect), color);
+                    Ok(())
+                }
+                Request::Blit { rect, pixels, stride } => blit(display, &rect, pixels, stride),
+                Request::Text { text, position, style } => {
+                    let Ok(_) = Text::new(text, position, style).draw(display);
+                    Ok(())
+                }
+                Request::Present => {
+                    present(display);
+                    Ok(())
+                }
+            };
+            if let Some(done) = queued.done {
+                done.signal(result);
+            }
+        }
+    }
+}
+
+/// Converts rectangle to embedded-graphics one
+fn to_area(rect: &Rect) -> Rectangle {
+    Rectangle::new(Point::new(rect.x, rect.y), Size::new(rect.width, rect.height))
+}
+
+/// Copies image region to display
+fn blit(
+    display: &mut DcsDisplay<'_>,
+    rect: &Rect,
+    pixels: &[u16],
+    stride: usize,
+) -> Result<(), DisplayError> {
+    if rect.is_empty() {
+        return Ok(());
+    }
+    check_source(rect, pixels.len(), stride)?;
+    let width = rect.width as usize;
+    let height = rect.height as usize;
+    let colors = pixels
+        .chunks(stride)
+        .take(height)
+        .flat_map(|row| &row[..width])
+        .map(|pixel| Rgb565::from(RawU16::new(*pixel)));
+    let Ok(()) = display.fill_contiguous(&to_area(rect), colors);
+    Ok(())
+}
+
+impl<M: RawMutex + 'static, const N: usize> Default for DisplayServer<M, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,84 @@
+//! Display shared between tasks
+//!
+//! [`FsmcLcd`] methods take `&self`, so nothing prevents two tasks from
+//! interleaving their writes, e.g. one task may write pixels between
+//! CASET/PASET and RAMWR commands of another one. [`SharedLcd`] puts
+//! interface behind embassy-sync mutex, and all access goes through
+//! transactions which hold the lock for the whole command and data sequence.
+//!
+//! Use [`CriticalSectionRawMutex`] when display is used from interrupt
+//! executors or from interrupt handlers, [`ThreadModeRawMutex`] when it is
+//! used from thread mode executor only.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embassy_stm32_fsmc_display_interface::FsmcLcd;
+//! use embassy_stm32_fsmc_display_interface::shared::SharedLcd;
+//! use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+//! # async fn run(lcd: &'static SharedLcd<'static, CriticalSectionRawMutex>) {
+//!
+//! // In any task
+//! lcd.transaction(|tx| {
+//!     tx.write_window(0, 0, 9, 9);
+//!     tx.fill_data(0xFFFF, 100);
+//! })
+//! .await;
+//! # }
+//! ```
+//!
+//! [`CriticalSectionRawMutex`]: embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex
+//! [`ThreadModeRawMutex`]: embassy_sync::blocking_mutex::raw::ThreadModeRawMutex
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::mutex::{Mutex, MutexGuard, TryLockError};
+
+use crate::FsmcLcd;
+
+/// Display interface protected by mutex
+pub struct SharedLcd<'d, M: RawMutex> {
+    lcd: Mutex<M, FsmcLcd<'d>>,
+}
+
+impl<'d, M: RawMutex> SharedLcd<'d, M> {
+    /// Wraps display interface
+    pub const fn new(lcd: FsmcLcd<'d>) -> Self {
+        Self {
+            lcd: Mutex::new(lcd),
+        }
+    }
+
+    /// Waits until display is free and runs `f` with exclusive access
+    ///
+    /// Everything `f` writes reaches display without being interleaved with
+    /// other transactions.
+    pub async fn transaction<R>(&self, f: impl FnOnce(&mut FsmcLcd<'d>) -> R) -> R {
+        let mut lcd = self.lcd.lock().await;
+        f(&mut lcd)
+    }
+
+    /// Runs `f` with exclusive access if display is free
+    ///
+    /// Unlike [`SharedLcd::transaction`] doesn't wait, so may be used from
+    /// interrupt handlers and blocking code.
+    pub fn try_transaction<R>(
+        &self,
+        f: impl FnOnce(&mut FsmcLcd<'d>) -> R,
+    ) -> Result<R, TryLockError> {
+        let mut lcd = self.lcd.try_lock()?;
+        Ok(f(&mut lcd))
+    }
+
+    /// Waits until display is free and locks it
+    ///
+    /// Display stays locked until guard is dropped, which allows awaiting
+    /// inside of a transaction, e.g. for DMA transfers.
+    pub async fn lock(&self) -> MutexGuard<'_, M, FsmcLcd<'d>> {
+        self.lcd.lock().await
+    }
+
+    /// Returns display interface
+    pub fn into_inner(self) -> FsmcLcd<'d> {
+        self.lcd.into_inner()
+    }
+}
@@ -0,0 +1,277 @@
+//! Slint platform integration
+//!
+//! [`FsmcPlatform`] is a Slint platform with single
+//! [`MinimalSoftwareWindow`], time is taken from embassy-time.
+//! [`LineBuffer`] is a line buffer provider for Slint software renderer: it
+//! collects consecutive rendered lines into batches and writes each batch
+//! into its display window. With DMA buffer is split into two halves, one
+//! of them is rendered while another is transferred.
+//!
+//! Slint requires global allocator.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embassy_stm32_fsmc_display_interface::FsmcLcd;
+//! use embassy_stm32_fsmc_display_interface::dma::FsmcDma;
+//! use embassy_stm32_fsmc_display_interface::slint::{FsmcPlatform, LineBuffer};
+//! use slint::platform::software_renderer::Rgb565Pixel;
+//! # async fn run(lcd: &FsmcLcd<'_>, dma: FsmcDma<'_>, buffer: &mut [Rgb565Pixel; 3200]) {
+//!
+//! let platform = FsmcPlatform::new(320, 240);
+//! let window = platform.window();
+//! slint::platform::set_platform(Box::new(platform)).unwrap();
+//! // Create UI components here
+//!
+//! let mut line_buffer = LineBuffer::with_dma(lcd, dma, buffer);
+//! loop {
+//!     slint::platform::update_timers_and_animations();
+//!     line_buffer.render(&window).unwrap();
+//!     embassy_time::Timer::after_millis(10).await;
+//! }
+//! # }
+//! ```
+
+use alloc::rc::Rc;
+use core::ops::Range;
+use core::time::Duration;
+
+use display_interface::DisplayError;
+use embassy_time::Instant;
+use ::slint::PhysicalSize;
+use ::slint::platform::software_renderer::{
+    LineBufferProvider, MinimalSoftwareWindow, RepaintBufferType, Rgb565Pixel,
+};
+use ::slint::platform::{Platform, WindowAdapter};
+
+use crate::FsmcLcd;
+use crate::dma::FsmcDma;
+
+/// Slint platform with single window
+pub struct FsmcPlatform {
+    window: Rc<MinimalSoftwareWindow>,
+    start: Instant,
+}
+
+impl FsmcPlatform {
+    /// Creates platform with window of given size
+    pub fn new(width: u32, height: u32) -> Self {
+        let window = MinimalSoftwareWindow::new(RepaintBufferType::ReusedBuffer);
+        window.set_size(PhysicalSize::new(width, height));
+        Self {
+            window,
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns platform window
+    pub fn window(&self) -> Rc<MinimalSoftwareWindow> {
+        self.window.clone()
+    }
+}
+
+impl Platform for FsmcPlatform {
+    fn create_window_adapter(&self) -> Result<Rc<dyn WindowAdapter>, ::slint::PlatformError> {
+        Ok(self.window.clone())
+    }
+
+    fn duration_since_start(&self) -> Duration {
+        Duration::from_micros(self.start.elapsed().as_micros())
+    }
+}
+
+/// Lines collected into single display window
+struct Batch {
+    first_line: usize,
+    range: Range<usize>,
+    lines: usize,
+}
+
+/// DMA transfer of buffer part in progress
+struct Pending {
+    buffer: usize,
+    started: usize,
+    len: usize,
+}
+
+/// Line buffer provider writing lines to display
+///
+/// Buffer must hold at least one full line of the window, or two lines when
+/// DMA is used. Lines which don't fit are skipped and reported as
+/// [`DisplayError::OutOfBoundsError`].
+pub struct LineBuffer<'a, 'd> {
+    lcd: &'a FsmcLcd<'d>,
+    dma: Option<FsmcDma<'d>>,
+    buffers: [&'a mut [Rgb565Pixel]; 2],
+    current: usize,
+    batch: Option<Batch>,
+    pending: Option<Pending>,
+    error: Option<DisplayError>,
+}
+
+impl<'a, 'd> LineBuffer<'a, 'd> {
+    /// Creates line buffer writing pixels by CPU
+    pub fn new(lcd: &'a FsmcLcd<'d>, buffer: &'a mut [Rgb565Pixel]) -> Self {
+        Self {
+            lcd,
+            dma: None,
+            buffers: [buffer, &mut []],
+            current: 0,
+            batch: None,
+            pending: None,
+            error: None,
+        }
+    }
+
+    /// Creates line buffer writing pixels by DMA, buffer is split into two
+    /// halves
+    pub fn with_dma(
+        lcd: &'a FsmcLcd<'d>,
+        dma: FsmcDma<'d>,
+        buffer: &'a mut [Rgb565Pixel],
+    ) -> Self {
+        let (first, second) = buffer.split_at_mut(buffer.len() / 2);
+        Self {
+            lcd,
+            dma: Some(dma),
+            buffers: [first, second],
+            current: 0,
+            batch: None,
+            pending: None,
+            error: None,
+        }
+    }
+
+    /// Renders window if needed and waits until all lines are written
+    ///
+    /// Returns whether window was redrawn, [`DisplayError::OutOfBoundsError`]
+    /// if buffer can't hold a line of the window or
+    /// [`DisplayError::BusWriteError`] if DMA transfer failed.
+    pub fn render(&mut self, window: &MinimalSoftwareWindow) -> Result<bool, DisplayError> {
+        if !self.fits(window.size().width as usize) {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        window.draw_if_needed(|renderer| {
+            renderer.render_by_line(&mut *self);
+        });
+        self.finish()
+    }
+
+    /// Whether every buffer can hold line of given width
+    fn fits(&self, width: usize) -> bool {
+        self.buffers[..self.dma.as_ref().map_or(1, |_| 2)]
+            .iter()
+            .all(|buffer| buffer.len() >= width)
+    }
+
+    /// Writes collected lines and waits until transfer is finished
+    ///
+    /// Must be called after rendering if [`LineBuffer`] is used with
+    /// `render_by_line` directly. Returns whether anything was written, or
+    /// the first error since the previous call. Lines whose transfer failed
+    /// or which didn't fit into buffer are skipped.
+    pub fn finish(&mut self) -> Result<bool, DisplayError> {
+        let written = self.batch.is_some() || self.pending.is_some();
+        self.write_batch();
+        self.wait_pending();
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(written),
+        }
+    }
+
+    /// Waits until pending DMA transfer is finished, remembering error
+    fn wait_pending(&mut self) {
+        let (Some(dma), Some(pending)) = (&mut self.dma, self.pending.take()) else {
+            return;
+        };
+        let Pending { buffer, mut started, len } = pending;
+        let result = loop {
+            if let Err(error) = dma.wait_blocking() {
+                break Err(error);
+            }
+            if started == len {
+                break Ok(());
+            }
+            // SAFETY: transfer is awaited before returning
+            let pixels = as_words(&self.buffers[buffer][started..len]);
+            match unsafe { dma.start(self.lcd.data_address(), pixels) } {
+                Ok(count) => started += count,
+                Err(error) => break Err(error),
+            }
+        };
+        if let Err(error) = result {
+            self.error.get_or_insert(error);
+        }
+    }
+
+    /// Writes collected lines into their window
+    fn write_batch(&mut self) {
+        let Some(batch) = self.batch.take() else {
+            return;
+        };
+        let len = batch.range.len() * batch.lines;
+        self.wait_pending();
+        self.lcd.write_window(
+            batch.range.start as u16,
+            batch.first_line as u16,
+            (batch.range.end - 1) as u16,
+            (batch.first_line + batch.lines - 1) as u16,
+        );
+
+        let pixels = as_words(&self.buffers[self.current][..len]);
+        match &mut self.dma {
+            Some(dma) => {
+                // SAFETY: buffer is not touched until transfer is finished,
+                // which happens at latest in `finish`
+                match unsafe { dma.start(self.lcd.data_address(), pixels) } {
+                    Ok(started) => {
+                        self.pending = Some(Pending { buffer: self.current, started, len });
+                        self.current = 1 - self.current;
+                    }
+                    Err(error) => {
+                        self.error.get_or_insert(error);
+                    }
+                }
+            }
+            None => {
+                for pixel in pixels {
+                    self.lcd.write_data(*pixel);
+                }
+            }
+        }
+    }
+}
+
+impl LineBufferProvider for &mut LineBuffer<'_, '_> {
+    type TargetPixel = Rgb565Pixel;
+
+    fn process_line(
+        &mut self,
+        line: usize,
+        range: Range<usize>,
+        render_fn: impl FnOnce(&mut [Self::TargetPixel]),
+    ) {
+        let width = range.len();
+        if !self.fits(width) {
+            self.error.get_or_insert(DisplayError::OutOfBoundsError);
+            return;
+        }
+        if let Some(batch) = &self.batch {
+            let fits = (batch.lines + 1) * width <= self.buffers[self.current].len();
+            if line != batch.first_line + batch.lines || range != batch.range || !fits {
+                self.write_batch();
+            }
+        }
+        let batch = self.batch.get_or_insert(Batch { first_line: line, range, lines: 0 });
+        let offset = batch.lines * width;
+        batch.lines += 1;
+        render_fn(&mut self.buffers[self.current][offset..offset + width]);
+    }
+}
+
+/// Reinterprets pixels as bus words
+fn as_words(pixels: &[Rgb565Pixel]) -> &[u16] {
+    // SAFETY: `Rgb565Pixel` is transparent wrapper around `u16`
+    unsafe { core::slice::from_raw_parts(pixels.as_ptr().cast(), pixels.len()) }
+}
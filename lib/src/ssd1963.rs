@@ -0,0 +1,300 @@
+//! SSD1963 display controller support
+//!
+//! SSD1963 drives RGB panels without built-in controller, so panel timings
+//! (PLL, pixel clock, sync and porch durations) must be programmed over the
+//! bus before anything is displayed. Until PLL is locked the controller runs
+//! from crystal clock and accepts only slow bus transfers, so setup uses slow
+//! timing first and switches to fast one afterwards.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embassy_stm32_fsmc_display_interface::FsmcLcd;
+//! use embassy_stm32_fsmc_display_interface::ssd1963::{self, PanelTiming, Ssd1963Config};
+//! # fn run(lcd: &mut FsmcLcd, delay: &mut impl embedded_hal::delay::DelayNs) {
+//!
+//! // Typical 4.3" 480x272 panel
+//! let config = Ssd1963Config {
+//!     panel: PanelTiming {
+//!         width: 480,
+//!         height: 272,
+//!         pixel_clock_hz: 9_000_000,
+//!         hsync_pulse: 41,
+//!         h_back_porch: 2,
+//!         h_front_porch: 2,
+//!         vsync_pulse: 10,
+//!         v_back_porch: 2,
+//!         v_front_porch: 2,
+//!     },
+//!     ..Ssd1963Config::new(10_000_000)
+//! };
+//! ssd1963::setup(lcd, &config, delay).unwrap();
+//! ssd1963::set_backlight(lcd, 255);
+//! # }
+//! ```
+
+use embassy_stm32::pac::fsmc::vals::Accmod;
+use embedded_hal::delay::DelayNs;
+
+use crate::dcs::{AddressMode, Command};
+use crate::{FsmcLcd, Timing};
+
+const SET_LCD_MODE: u8 = 0xB0;
+const SET_HORI_PERIOD: u8 = 0xB4;
+const SET_VERT_PERIOD: u8 = 0xB6;
+const SET_PWM_CONF: u8 = 0xBE;
+const SET_PLL: u8 = 0xE0;
+const SET_PLL_MN: u8 = 0xE2;
+const SET_LSHIFT_FREQ: u8 = 0xE6;
+const SET_PIXEL_DATA_INTERFACE: u8 = 0xF0;
+
+/// Allowed VCO frequency range
+const VCO_MIN_HZ: u64 = 250_000_000;
+const VCO_MAX_HZ: u64 = 800_000_000;
+
+/// Maximum value of pixel clock divider (LCDC_FPR), it is 20 bits wide
+const FPR_MAX: u64 = (1 << 20) - 1;
+
+/// Timing parameters of the RGB panel, see panel datasheet
+///
+/// All horizontal values are in pixel clocks, vertical values are in lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PanelTiming {
+    /// Horizontal resolution
+    pub width: u16,
+    /// Vertical resolution
+    pub height: u16,
+    /// Pixel clock frequency
+    pub pixel_clock_hz: u32,
+    /// HSYNC pulse width
+    pub hsync_pulse: u16,
+    /// Horizontal back porch
+    pub h_back_porch: u16,
+    /// Horizontal front porch
+    pub h_front_porch: u16,
+    /// VSYNC pulse width
+    pub vsync_pulse: u16,
+    /// Vertical back porch
+    pub v_back_porch: u16,
+    /// Vertical front porch
+    pub v_front_porch: u16,
+}
+
+/// SSD1963 setup parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ssd1963Config {
+    /// Crystal (reference clock) frequency
+    pub crystal_hz: u32,
+    /// Desired PLL frequency, actual one is the closest frequency not
+    /// exceeding it
+    pub pll_hz: u32,
+    /// Panel timings
+    pub panel: PanelTiming,
+    /// Whether panel has 24-bit data bus, 18-bit otherwise
+    pub panel_24bit: bool,
+    /// Memory access mode, sets orientation
+    pub address_mode: AddressMode,
+    /// Bus timing used before PLL is locked
+    pub init_timing: Timing,
+    /// Bus write timing used after PLL is locked
+    pub write_timing: Timing,
+}
+
+impl Ssd1963Config {
+    /// Maximum PLL frequency
+    pub const PLL_MAX_HZ: u32 = 110_000_000;
+
+    /// Creates configuration for given crystal frequency
+    ///
+    /// Panel timings are zeroed and must be filled in.
+    pub const fn new(crystal_hz: u32) -> Self {
+        Self {
+            crystal_hz,
+            pll_hz: Self::PLL_MAX_HZ,
+            panel: PanelTiming {
+                width: 0,
+                height: 0,
+                pixel_clock_hz: 0,
+                hsync_pulse: 0,
+                h_back_porch: 0,
+                h_front_porch: 0,
+                vsync_pulse: 0,
+                v_back_porch: 0,
+                v_front_porch: 0,
+            },
+            panel_24bit: true,
+            address_mode: AddressMode::new(),
+            init_timing: Timing::new(
+                Accmod::C,
+                Timing::BUS_TURNAROUND_MAX,
+                255,
+                Timing::ADDRESS_HOLD_MAX,
+                Timing::ADDRESS_SETUP_MAX,
+            ),
+            write_timing: Timing::new(Accmod::A, 1, 3, Timing::ADDRESS_HOLD_MIN, 1),
+        }
+    }
+}
+
+/// SSD1963 setup error
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ssd1963Error {
+    /// No PLL multiplier and divider give valid frequency
+    PllOutOfRange,
+    /// Pixel clock is zero or above PLL frequency
+    PixelClockOutOfRange,
+    /// Panel size or timings don't fit controller registers
+    InvalidPanelTiming,
+}
+
+/// PLL multiplier (M) and divider (N) register values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct PllParams {
+    m: u8,
+    n: u8,
+    pll_hz: u64,
+}
+
+/// Finds PLL parameters giving the highest frequency not exceeding `target`
+fn pll_params(crystal_hz: u32, target_hz: u32) -> Option<PllParams> {
+    let target = u64::from(target_hz.min(Ssd1963Config::PLL_MAX_HZ));
+    let mut best: Option<PllParams> = None;
+    for m in 0..=u8::MAX {
+        let vco = u64::from(crystal_hz) * (u64::from(m) + 1);
+        if !(VCO_MIN_HZ..=VCO_MAX_HZ).contains(&vco) {
+            continue;
+        }
+        for n in 0..32 {
+            let pll_hz = vco / (u64::from(n) + 1);
+            if pll_hz <= target && best.is_none_or(|best| pll_hz > best.pll_hz) {
+                best = Some(PllParams { m, n, pll_hz });
+            }
+        }
+    }
+    best
+}
+
+/// Computes pixel clock divider: PCLK = PLL * (LCDC_FPR + 1) / 2^20
+fn lshift_freq(pll_hz: u64, pixel_clock_hz: u32) -> Option<u32> {
+    let pixel_clock_hz = u64::from(pixel_clock_hz);
+    if pixel_clock_hz == 0 || pixel_clock_hz > pll_hz {
+        return None;
+    }
+    let fpr = (pixel_clock_hz << 20) / pll_hz;
+    Some(fpr.saturating_sub(1).min(FPR_MAX) as u32)
+}
+
+/// Sends command with 8-bit parameters
+fn send(lcd: &FsmcLcd<'_>, cmd: u8, params: &[u8]) {
+    lcd.write_command(u16::from(cmd));
+    for param in params {
+        lcd.write_data(u16::from(*param));
+    }
+}
+
+/// Sets up SSD1963 for the panel
+///
+/// Bus timing is set to `config.init_timing` for both reads and writes,
+/// after PLL is locked write timing is switched to `config.write_timing`.
+/// Read timing is left slow if separate write timing is configured,
+/// otherwise reads use write timing too, use [`FsmcLcd::with_read_timing`]
+/// for reads then. Pixel data interface is set to 16-bit RGB565, display is
+/// turned on.
+pub fn setup(
+    lcd: &mut FsmcLcd<'_>,
+    config: &Ssd1963Config,
+    delay: &mut impl DelayNs,
+) -> Result<(), Ssd1963Error> {
+    let panel = &config.panel;
+    let pll = pll_params(config.crystal_hz, config.pll_hz).ok_or(Ssd1963Error::PllOutOfRange)?;
+    let fpr = lshift_freq(pll.pll_hz, panel.pixel_clock_hz)
+        .ok_or(Ssd1963Error::PixelClockOutOfRange)?;
+
+    let h_total = u32::from(panel.width)
+        + u32::from(panel.hsync_pulse)
+        + u32::from(panel.h_back_porch)
+        + u32::from(panel.h_front_porch);
+    let v_total = u32::from(panel.height)
+        + u32::from(panel.vsync_pulse)
+        + u32::from(panel.v_back_porch)
+        + u32::from(panel.v_front_porch);
+    if panel.width == 0
+        || panel.height == 0
+        || panel.hsync_pulse == 0
+        || panel.vsync_pulse == 0
+        || h_total > 0x1000
+        || v_total > 0x1000
+        || panel.hsync_pulse > 0x80
+        || panel.vsync_pulse > 0x80
+    {
+        return Err(Ssd1963Error::InvalidPanelTiming);
+    }
+    let h_start = panel.hsync_pulse + panel.h_back_porch;
+    let v_start = panel.vsync_pulse + panel.v_back_porch;
+
+    // Controller runs from crystal until PLL is locked
+    lcd.set_read_timing(&config.init_timing);
+    lcd.set_write_timing(&config.init_timing);
+
+    // Third parameter validates M and N values
+    send(lcd, SET_PLL_MN, &[pll.m, pll.n, 0x54]);
+    // Enable PLL, wait for it to stabilize, then use it as system clock
+    send(lcd, SET_PLL, &[0x01]);
+    delay.delay_us(100);
+    send(lcd, SET_PLL, &[0x03]);
+    delay.delay_us(100);
+    lcd.write_dcs(Command::SoftReset);
+    delay.delay_ms(5);
+
+    lcd.set_write_timing(&config.write_timing);
+
+    let fpr = fpr.to_be_bytes();
+    send(lcd, SET_LSHIFT_FREQ, &fpr[1..]);
+
+    let [width_hi, width_lo] = (panel.width - 1).to_be_bytes();
+    let [height_hi, height_lo] = (panel.height - 1).to_be_bytes();
+    let data_width = if config.panel_24bit { 0x20 } else { 0x00 };
+    // TFT mode, default signal polarities
+    send(
+        lcd,
+        SET_LCD_MODE,
+        &[data_width, 0x00, width_hi, width_lo, height_hi, height_lo, 0x00],
+    );
+
+    let [ht_hi, ht_lo] = ((h_total - 1) as u16).to_be_bytes();
+    let [hps_hi, hps_lo] = h_start.to_be_bytes();
+    send(
+        lcd,
+        SET_HORI_PERIOD,
+        &[ht_hi, ht_lo, hps_hi, hps_lo, (panel.hsync_pulse - 1) as u8, 0x00, 0x00, 0x00],
+    );
+
+    let [vt_hi, vt_lo] = ((v_total - 1) as u16).to_be_bytes();
+    let [vps_hi, vps_lo] = v_start.to_be_bytes();
+    send(
+        lcd,
+        SET_VERT_PERIOD,
+        &[vt_hi, vt_lo, vps_hi, vps_lo, (panel.vsync_pulse - 1) as u8, 0x00, 0x00],
+    );
+
+    // 16-bit RGB565 pixel data
+    send(lcd, SET_PIXEL_DATA_INTERFACE, &[0x03]);
+    lcd.write_dcs(Command::SetAddressMode(config.address_mode));
+    lcd.write_dcs(Command::SetDisplayOn);
+
+    if config.address_mode.is_swap_xy() {
+        lcd.set_panel_size(panel.height, panel.width);
+    } else {
+        lcd.set_panel_size(panel.width, panel.height);
+    }
+    Ok(())
+}
+
+/// Sets backlight brightness using SSD1963 PWM output
+///
+/// 0 turns backlight off, 255 is full brightness.
+pub fn set_backlight(lcd: &FsmcLcd<'_>, brightness: u8) {
+    // PWM frequency divider, PWM enabled and controlled by host, manual
+    // brightness at maximum
+    send(lcd, SET_PWM_CONF, &[0x06, brightness, 0x01, 0xFF, 0x00, 0x00]);
+}
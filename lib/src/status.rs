@@ -0,0 +1,46 @@
+use embassy_time::{Duration, Instant, Timer};
+
+use crate::FsmcLcd;
+
+/// Status didn't reach expected value in time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatusTimeout {
+    /// Last status value read
+    pub status: u16,
+}
+
+impl<'d> FsmcLcd<'d> {
+    /// Waits until masked status register equals `value`
+    ///
+    /// Status is read with [`FsmcLcd::read_status`] once per timer tick.
+    /// Returns the last status value read, or error if it doesn't match
+    /// within `timeout`.
+    ///
+    /// ```no_run
+    /// # use embassy_stm32_fsmc_display_interface::FsmcLcd;
+    /// # use embassy_time::Duration;
+    /// # async fn run(lcd: &FsmcLcd<'_>) {
+    /// // Wait until RA8875 finishes drawing
+    /// const BUSY: u16 = 0x80;
+    /// lcd.wait_status(BUSY, 0, Duration::from_millis(100)).await.unwrap();
+    /// # }
+    /// ```
+    pub async fn wait_status(
+        &self,
+        mask: u16,
+        value: u16,
+        timeout: Duration,
+    ) -> Result<u16, StatusTimeout> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let status = self.read_status();
+            if status & mask == value {
+                return Ok(status);
+            }
+            if Instant::now() >= deadline {
+                return Err(StatusTimeout { status });
+            }
+            Timer::after_ticks(1).await;
+        }
+    }
+}
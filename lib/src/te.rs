@@ -0,0 +1,78 @@
+//! Tearing effect (TE) synchronization
+//!
+//! Controller signals on its TE output when it is not reading display
+//! memory to refresh the panel. Starting frame write right after TE edge
+//! avoids tearing during animations, as long as the write is faster than
+//! panel refresh.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embassy_stm32::exti::ExtiInput;
+//! use embassy_stm32_fsmc_display_interface::FsmcLcd;
+//! use embassy_stm32_fsmc_display_interface::dcs::TearingMode;
+//! use embassy_stm32_fsmc_display_interface::te::TearingSync;
+//! # async fn run(lcd: &mut FsmcLcd<'_>, te_pin: ExtiInput<'_>) {
+//!
+//! let mut te = TearingSync::new(te_pin);
+//! te.enable(lcd, TearingMode::VBlank);
+//! loop {
+//!     te.present(lcd, |lcd| {
+//!         lcd.write_window(0, 0, 319, 239);
+//!         // Stream frame data here
+//!     }).await;
+//! }
+//! # }
+//! ```
+
+use embassy_stm32::exti::ExtiInput;
+
+use crate::dcs::{Command, TearingMode};
+use crate::FsmcLcd;
+
+/// TE input synchronization
+pub struct TearingSync<'d> {
+    pin: ExtiInput<'d>,
+}
+
+impl<'d> TearingSync<'d> {
+    /// Creates synchronization using EXTI input connected to TE output
+    pub fn new(pin: ExtiInput<'d>) -> Self {
+        Self { pin }
+    }
+
+    /// Enables TE output of the controller
+    pub fn enable(&self, lcd: &FsmcLcd<'_>, mode: TearingMode) {
+        lcd.write_dcs(Command::SetTearOn(mode));
+    }
+
+    /// Disables TE output of the controller
+    pub fn disable(&self, lcd: &FsmcLcd<'_>) {
+        lcd.write_dcs(Command::SetTearOff);
+    }
+
+    /// Makes controller signal TE when it reaches `scanline`
+    ///
+    /// Starting write on a scanline instead of vertical blanking gives more
+    /// time for the write, if it is done in the same direction as refresh
+    /// and is faster than it.
+    pub fn enable_at_scanline(&self, lcd: &FsmcLcd<'_>, scanline: u16) {
+        lcd.write_dcs(Command::SetTearScanline(scanline));
+        lcd.write_dcs(Command::SetTearOn(TearingMode::VBlank));
+    }
+
+    /// Waits for the start of vertical blanking (or configured scanline)
+    pub async fn wait_for_vblank(&mut self) {
+        self.pin.wait_for_rising_edge().await;
+    }
+
+    /// Waits for vertical blanking and then runs `f` writing the frame
+    pub async fn present<R>(
+        &mut self,
+        lcd: &mut FsmcLcd<'_>,
+        f: impl FnOnce(&mut FsmcLcd<'_>) -> R,
+    ) -> R {
+        self.wait_for_vblank().await;
+        f(lcd)
+    }
+}